@@ -0,0 +1,18 @@
+//! Deterministic inputs shared by the benchmarks.
+
+/// A xorshift pseudo-random number generator with a fixed seed, so that runs are comparable.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new() -> Self {
+        Self(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Gets the next pseudo-random word.
+    pub fn next_word(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
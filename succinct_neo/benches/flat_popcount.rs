@@ -0,0 +1,68 @@
+use std::hint::black_box;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use succinct_neo::{
+    bit_vec::BitVec,
+    rank_select::{FlatPopcount, RankSupport},
+};
+
+mod common;
+
+/// Creates a bit vector of the given length with about half of the bits set.
+fn random_bits(len: usize) -> BitVec {
+    let mut rng = Rng::new();
+    (0..len).map(|_| rng.next_word() & 1 == 1).collect()
+}
+
+fn construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flat_popcount_new");
+    for exp in [20, 26] {
+        let bv = random_bits(1 << exp);
+        group.throughput(Throughput::Bytes(bv.len() as u64 / 8));
+        group.bench_with_input(BenchmarkId::new("bit_vec", exp), &bv, |b, bv| {
+            b.iter(|| FlatPopcount::<_, ()>::new(black_box(bv)))
+        });
+        // Words of slices which do not start at a word boundary have to be assembled first
+        group.bench_with_input(BenchmarkId::new("unaligned_slice", exp), &bv, |b, bv| {
+            b.iter(|| FlatPopcount::<_, ()>::new(black_box(bv.slice(3..))))
+        });
+    }
+    group.finish();
+}
+
+/// Rebuilds the L1/L2 index without the select samples, which dominate the construction time.
+fn index(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flat_popcount_index");
+    let bv = random_bits(1 << 26);
+    group.throughput(Throughput::Bytes(bv.len() as u64 / 8));
+    let pop = FlatPopcount::<_, ()>::new(&bv);
+    group.bench_function("bit_vec", |b| b.iter(|| black_box(&pop).verify()));
+    let pop = FlatPopcount::<_, ()>::new(bv.slice(3..));
+    group.bench_function("unaligned_slice", |b| b.iter(|| black_box(&pop).verify()));
+    group.finish();
+}
+
+fn rank(c: &mut Criterion) {
+    let bv = random_bits(1 << 26);
+    let pop = FlatPopcount::<_, ()>::new(&bv);
+    let mut rng = Rng::new();
+    let queries: Vec<usize> = (0..10_000)
+        .map(|_| rng.next_word() as usize % bv.len())
+        .collect();
+
+    let mut group = c.benchmark_group("flat_popcount_rank");
+    group.throughput(Throughput::Elements(queries.len() as u64));
+    group.bench_function("rank_ones", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .map(|&i| pop.rank::<true>(black_box(i)))
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, construction, index, rank);
+criterion_main!(benches);
@@ -0,0 +1,132 @@
+use std::hint::black_box;
+
+use common::Rng;
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use succinct_neo::int_vec::{FixedIntVec, IntAccess, IntVec};
+
+mod common;
+
+/// The number of integers in each vector.
+const LEN: usize = 1 << 20;
+
+/// Creates pseudo-random integers of the given width.
+fn random_values(width: usize, len: usize) -> Vec<usize> {
+    let mask = usize::MAX >> (usize::BITS as usize - width);
+    let mut rng = Rng::new();
+    (0..len).map(|_| rng.next_word() as usize & mask).collect()
+}
+
+/// Creates pseudo-random indices into a vector of length [`LEN`].
+fn random_indices(len: usize) -> Vec<usize> {
+    random_values(usize::BITS as usize, len)
+        .into_iter()
+        .map(|i| i % LEN)
+        .collect()
+}
+
+fn filled<const W: usize>(values: &[usize]) -> (FixedIntVec<W>, IntVec) {
+    let mut fixed = FixedIntVec::<W>::with_capacity(values.len());
+    let mut dynamic = IntVec::with_capacity(W, values.len());
+    for &value in values {
+        fixed.push(value);
+        dynamic.push(value);
+    }
+    (fixed, dynamic)
+}
+
+fn get_width<const W: usize>(group: &mut BenchmarkGroup<WallTime>, indices: &[usize]) {
+    let (fixed, dynamic) = filled::<W>(&random_values(W, LEN));
+    group.bench_function(BenchmarkId::new("fixed", W), |b| {
+        b.iter(|| {
+            indices
+                .iter()
+                .map(|&i| fixed.get(black_box(i)))
+                .sum::<usize>()
+        })
+    });
+    group.bench_function(BenchmarkId::new("dynamic", W), |b| {
+        b.iter(|| {
+            indices
+                .iter()
+                .map(|&i| dynamic.get(black_box(i)))
+                .sum::<usize>()
+        })
+    });
+}
+
+fn set_width<const W: usize>(group: &mut BenchmarkGroup<WallTime>, indices: &[usize]) {
+    let values = random_values(W, indices.len());
+    let (mut fixed, mut dynamic) = filled::<W>(&random_values(W, LEN));
+    group.bench_function(BenchmarkId::new("fixed", W), |b| {
+        b.iter(|| {
+            for (&i, &value) in indices.iter().zip(&values) {
+                fixed.set(black_box(i), value);
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::new("dynamic", W), |b| {
+        b.iter(|| {
+            for (&i, &value) in indices.iter().zip(&values) {
+                dynamic.set(black_box(i), value);
+            }
+        })
+    });
+}
+
+fn push_width<const W: usize>(group: &mut BenchmarkGroup<WallTime>) {
+    let values = random_values(W, LEN);
+    group.bench_function(BenchmarkId::new("fixed", W), |b| {
+        b.iter(|| {
+            let mut v = FixedIntVec::<W>::new();
+            for &value in &values {
+                v.push(black_box(value));
+            }
+            v
+        })
+    });
+    group.bench_function(BenchmarkId::new("dynamic", W), |b| {
+        b.iter(|| {
+            let mut v = IntVec::new(W);
+            for &value in &values {
+                v.push(black_box(value));
+            }
+            v
+        })
+    });
+}
+
+fn get(c: &mut Criterion) {
+    let indices = random_indices(10_000);
+    let mut group = c.benchmark_group("int_vec_get");
+    group.throughput(Throughput::Elements(indices.len() as u64));
+    // 16 divides the word size, so FixedIntVec never handles integers spanning two words
+    get_width::<7>(&mut group, &indices);
+    get_width::<16>(&mut group, &indices);
+    get_width::<31>(&mut group, &indices);
+    group.finish();
+}
+
+fn set(c: &mut Criterion) {
+    let indices = random_indices(10_000);
+    let mut group = c.benchmark_group("int_vec_set");
+    group.throughput(Throughput::Elements(indices.len() as u64));
+    set_width::<7>(&mut group, &indices);
+    set_width::<16>(&mut group, &indices);
+    set_width::<31>(&mut group, &indices);
+    group.finish();
+}
+
+fn push(c: &mut Criterion) {
+    let mut group = c.benchmark_group("int_vec_push");
+    group.throughput(Throughput::Elements(LEN as u64));
+    push_width::<7>(&mut group);
+    push_width::<16>(&mut group);
+    push_width::<31>(&mut group);
+    group.finish();
+}
+
+criterion_group!(benches, get, set, push);
+criterion_main!(benches);
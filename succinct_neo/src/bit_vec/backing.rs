@@ -1,5 +1,29 @@
-use super::{BitGet, BitModify};
-use super::{WORD_EXP, WORD_MASK};
+use alloc::vec::Vec;
+
+use super::traits::check_bits_value;
+use super::{BitGet, BitModify, Word, WordAccess, WORD_SIZE};
+use crate::traits::BlockType;
+
+/// Creates a mask covering the lowest `len` bits of a word.
+#[inline]
+const fn low_mask(len: usize) -> Word {
+    if len >= WORD_SIZE {
+        Word::MAX
+    } else {
+        (1 << len) - 1
+    }
+}
+
+/// Panics if `len` exceeds the word size or the bit range is out of bounds of `bit_len` bits.
+#[inline]
+fn check_bits_range(index: usize, len: usize, bit_len: usize) {
+    if len > WORD_SIZE {
+        panic!("length is {len} but at most {WORD_SIZE} bits can be accessed at once")
+    }
+    if index + len > bit_len {
+        panic!("range end is {} but length is {bit_len}", index + len)
+    }
+}
 
 macro_rules! primitive_bit_ops {
     {$tp:ty} => {
@@ -11,8 +35,8 @@ macro_rules! primitive_bit_ops {
 
             #[inline]
             fn get_bit(&self, index: usize) -> bool {
-                if index >= std::mem::size_of::<Self>() * 8 {
-                    panic!("index is {index} but length is {}", std::mem::size_of::<Self>() * 8)
+                if index >= core::mem::size_of::<Self>() * 8 {
+                    panic!("index is {index} but length is {}", core::mem::size_of::<Self>() * 8)
                 }
 
                 // SAFETY: We checked the index is in bounds
@@ -32,8 +56,8 @@ macro_rules! primitive_bit_ops {
 
             #[inline]
             fn set_bit(&mut self, index: usize, value: bool) {
-                if index >= std::mem::size_of::<Self>() * 8 {
-                    panic!("index is {index} but length is {}", std::mem::size_of::<Self>() * 8)
+                if index >= core::mem::size_of::<Self>() * 8 {
+                    panic!("index is {index} but length is {}", core::mem::size_of::<Self>() * 8)
                 }
                 // SAFETY: We checked the index is in bounds
                 unsafe { self.set_bit_unchecked(index, value) }
@@ -46,8 +70,8 @@ macro_rules! primitive_bit_ops {
 
             #[inline]
             fn flip_bit(&mut self, index: usize) {
-                if index >= std::mem::size_of::<Self>() * 8 {
-                    panic!("index is {index} but length is {}", std::mem::size_of::<Self>() * 8)
+                if index >= core::mem::size_of::<Self>() * 8 {
+                    panic!("index is {index} but length is {}", core::mem::size_of::<Self>() * 8)
                 }
                 // SAFETY: We checked the index is in bounds
                 unsafe { self.flip_bit_unchecked(index) }
@@ -61,62 +85,219 @@ macro_rules! primitive_bit_ops {
 }
 
 primitive_bit_ops!{
-    u8, u16, u32, u64
+    u8, u16, u32, u64, u128
 }
 
 primitive_bit_ops!{ usize }
 
-impl BitGet for [usize] {
+/// Splits a bit index into the index of the block containing it and the index inside the block.
+#[inline]
+fn locate<B: BlockType>(index: usize) -> (usize, usize) {
+    (index / B::BITS, index % B::BITS)
+}
+
+impl<B: BlockType> BitGet for [B] {
     #[inline]
     unsafe fn get_bit_unchecked(&self, index: usize) -> bool {
-        let block_index = index >> WORD_EXP;
-
-        let internal_index = index & WORD_MASK;
-        unsafe { self.get_unchecked(block_index).get_bit_unchecked(internal_index) }
+        let (block_index, internal_index) = locate::<B>(index);
+        unsafe { (*self.get_unchecked(block_index) >> internal_index) & B::one() == B::one() }
     }
 
     #[inline]
     fn get_bit(&self, index: usize) -> bool {
-        if index >= self.len() << WORD_EXP {
-            panic!("index is {index} but length is {}", self.len() << WORD_EXP)
+        if index >= self.len() * B::BITS {
+            panic!("index is {index} but length is {}", self.len() * B::BITS)
         }
         unsafe { self.get_bit_unchecked(index) }
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        if len == 0 {
+            return 0;
+        }
+        let (mut block_index, internal_index) = locate::<B>(index);
+
+        let mut value = unsafe { *self.get_unchecked(block_index) >> internal_index }.to_word();
+        // Collect the bits from the following blocks until there are enough
+        let mut read = B::BITS - internal_index;
+        while read < len {
+            block_index += 1;
+            value |= unsafe { *self.get_unchecked(block_index) }.to_word() << read;
+            read += B::BITS;
+        }
+        value & low_mask(len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        check_bits_range(index, len, self.len() * B::BITS);
+        unsafe { self.get_bits_unchecked(index, len) }
+    }
 }
 
-impl BitModify for [usize] {
+impl<B: BlockType> BitModify for [B] {
     unsafe fn set_bit_unchecked(&mut self, index: usize, value: bool) {
-        let block_index = index >> WORD_EXP;
-        let internal_index = index & WORD_MASK;
-
-        unsafe { self.get_unchecked_mut(block_index).set_bit_unchecked(internal_index, value) };
+        let (block_index, internal_index) = locate::<B>(index);
+        let block = unsafe { self.get_unchecked_mut(block_index) };
+        if value {
+            *block |= B::one() << internal_index
+        } else {
+            *block &= !(B::one() << internal_index)
+        }
     }
 
     fn set_bit(&mut self, index: usize, value: bool) {
-        if index >= self.len() << WORD_EXP {
-            panic!("index is {index} but length is {}", self.len() << WORD_EXP)
+        if index >= self.len() * B::BITS {
+            panic!("index is {index} but length is {}", self.len() * B::BITS)
         }
         unsafe { self.set_bit_unchecked(index, value) }
     }
 
     unsafe fn flip_bit_unchecked(&mut self, index: usize) {
-        let block_index = index >> WORD_EXP;
-        let internal_index = index & WORD_MASK;
-
-        unsafe { self.get_unchecked_mut(block_index).flip_bit_unchecked(internal_index) }
+        let (block_index, internal_index) = locate::<B>(index);
+        unsafe { *self.get_unchecked_mut(block_index) ^= B::one() << internal_index }
     }
 
     fn flip_bit(&mut self, index: usize) {
-        if index >= self.len() << WORD_EXP {
-            panic!("index is {index} but length is {}", self.len() << WORD_EXP)
+        if index >= self.len() * B::BITS {
+            panic!("index is {index} but length is {}", self.len() * B::BITS)
         }
         unsafe { self.flip_bit_unchecked(index) }
     }
+
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        let (mut block_index, mut internal_index) = locate::<B>(index);
+        let mut written = 0;
+        // Write the bits one block at a time
+        while written < len {
+            let fitting_bits = (B::BITS - internal_index).min(len - written);
+            let mask = B::from_word(low_mask(fitting_bits)) << internal_index;
+            let bits = B::from_word(value >> written) << internal_index;
+            unsafe {
+                let block = self.get_unchecked_mut(block_index);
+                *block = (*block & !mask) | (bits & mask);
+            }
+            written += fitting_bits;
+            block_index += 1;
+            internal_index = 0;
+        }
+    }
+
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        check_bits_range(index, len, self.len() * B::BITS);
+        check_bits_value(len, value);
+        unsafe { self.set_bits_unchecked(index, len, value) }
+    }
+}
+
+impl<B: BlockType> BitGet for Vec<B> {
+    #[inline]
+    unsafe fn get_bit_unchecked(&self, index: usize) -> bool {
+        self.as_slice().get_bit_unchecked(index)
+    }
+
+    #[inline]
+    fn get_bit(&self, index: usize) -> bool {
+        self.as_slice().get_bit(index)
+    }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        self.as_slice().get_bits_unchecked(index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        self.as_slice().get_bits(index, len)
+    }
+}
+
+impl<B: BlockType> BitModify for Vec<B> {
+    #[inline]
+    unsafe fn set_bit_unchecked(&mut self, index: usize, value: bool) {
+        self.as_mut_slice().set_bit_unchecked(index, value)
+    }
+
+    #[inline]
+    fn set_bit(&mut self, index: usize, value: bool) {
+        self.as_mut_slice().set_bit(index, value)
+    }
+
+    #[inline]
+    unsafe fn flip_bit_unchecked(&mut self, index: usize) {
+        self.as_mut_slice().flip_bit_unchecked(index)
+    }
+
+    #[inline]
+    fn flip_bit(&mut self, index: usize) {
+        self.as_mut_slice().flip_bit(index)
+    }
+
+    #[inline]
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        self.as_mut_slice().set_bits_unchecked(index, len, value)
+    }
+
+    #[inline]
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        self.as_mut_slice().set_bits(index, len, value)
+    }
+}
+
+/// Blocks are combined into or split up into words, so the words of a slice of blocks are the same
+/// as if its bits were stored in words. If the last word is not covered by blocks completely, its
+/// remaining bits are zero.
+impl<B: BlockType> WordAccess for [B] {
+    #[inline]
+    fn bit_len(&self) -> usize {
+        self.len() * B::BITS
+    }
+
+    #[inline]
+    fn word_len(&self) -> usize {
+        self.bit_len().div_ceil(WORD_SIZE)
+    }
+
+    #[inline]
+    unsafe fn get_word_unchecked(&self, index: usize) -> Word {
+        if B::BITS >= WORD_SIZE {
+            let words_per_block = B::BITS / WORD_SIZE;
+            let block = unsafe { *self.get_unchecked(index / words_per_block) };
+            (block >> (index % words_per_block * WORD_SIZE)).to_word()
+        } else {
+            let blocks_per_word = WORD_SIZE / B::BITS;
+            let start = index * blocks_per_word;
+            let end = self.len().min(start + blocks_per_word);
+            unsafe { self.get_unchecked(start..end) }
+                .iter()
+                .enumerate()
+                .fold(0, |word, (i, block)| word | block.to_word() << (i * B::BITS))
+        }
+    }
+}
+
+impl<B: BlockType> WordAccess for Vec<B> {
+    #[inline]
+    fn bit_len(&self) -> usize {
+        self.as_slice().bit_len()
+    }
+
+    #[inline]
+    fn word_len(&self) -> usize {
+        self.as_slice().word_len()
+    }
+
+    #[inline]
+    unsafe fn get_word_unchecked(&self, index: usize) -> Word {
+        self.as_slice().get_word_unchecked(index)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::bit_vec::{BitGet, BitModify};
+    use crate::bit_vec::{BitGet, BitModify, WordAccess};
+    use crate::traits::BlockType;
 
     macro_rules! test_primitive {
         {$tp:ty} => {
@@ -124,10 +305,10 @@ mod test {
                 #[test]
                 pub fn [<$tp _set_get_bit_test>]() {
                     let mut n = 0 as $tp;
-                    for i in 0..std::mem::size_of::<$tp>() * 8 {
+                    for i in 0..core::mem::size_of::<$tp>() * 8 {
                         n.set_bit(i, i % 2 == 0);
                     }
-                    for i in 0..std::mem::size_of::<$tp>() * 8 {
+                    for i in 0..core::mem::size_of::<$tp>() * 8 {
                         assert_eq!(i % 2 == 0, n.get_bit(i))
                     }
                 }
@@ -136,26 +317,26 @@ mod test {
                 #[should_panic]
                 pub fn [<$tp _get_bit_out_of_bounds_test>]() {
                     let n = 0 as $tp;
-                    n.get_bit(std::mem::size_of::<$tp>() * 8);
+                    n.get_bit(core::mem::size_of::<$tp>() * 8);
                 }
 
                 #[test]
                 #[should_panic]
                 pub fn [<$tp _set_bit_out_of_bounds_test>]() {
                     let mut n = 0 as $tp;
-                    n.set_bit(std::mem::size_of::<$tp>() * 8, true);
+                    n.set_bit(core::mem::size_of::<$tp>() * 8, true);
                 }
 
                 #[test]
                 pub fn [<$tp _flip_bit_test>]() {
                     let mut n = 0 as $tp;
-                    for i in 0..std::mem::size_of::<$tp>() * 8 {
+                    for i in 0..core::mem::size_of::<$tp>() * 8 {
                         n.set_bit(i, i % 2 == 0);
                     }
-                    for i in 0..std::mem::size_of::<$tp>() * 8 {
+                    for i in 0..core::mem::size_of::<$tp>() * 8 {
                         n.flip_bit(i);
                     }
-                    for i in 0..std::mem::size_of::<$tp>() * 8 {
+                    for i in 0..core::mem::size_of::<$tp>() * 8 {
                         assert_eq!(i % 2 == 1, n.get_bit(i), "index {i}")
                     }
                 }
@@ -164,7 +345,7 @@ mod test {
                 #[should_panic]
                 pub fn [<$tp _flip_bit_out_of_bounds_test>]() {
                     let mut n = 0 as $tp;
-                    n.flip_bit(std::mem::size_of::<$tp>() * 8);
+                    n.flip_bit(core::mem::size_of::<$tp>() * 8);
                 }
             }
         };
@@ -175,12 +356,12 @@ mod test {
     }
 
     test_primitive!{
-        u8, u16, u32, u64, usize
+        u8, u16, u32, u64, u128, usize
     }
 
     #[test]
     fn op_test() {
-        let mut slice = [0b1100_1100_1010_1010usize];
+        let mut slice = [0b1100_1100_1010_1010u64];
 
         for i in 0..slice.len() {
             slice.set_bit(i, if i < 8 { i % 2 == 1 } else { (i / 2) % 2 == 1 })
@@ -199,24 +380,142 @@ mod test {
         }
     }
 
+    #[test]
+    fn bits_test() {
+        let mut slice = [0u64; 3];
+
+        slice.set_bits(60, 8, 0b1011_0001);
+        assert_eq!(0b0001 << 60, slice[0], "lower block incorrect");
+        assert_eq!(0b1011, slice[1], "upper block incorrect");
+        assert_eq!(0b1011_0001, slice.get_bits(60, 8));
+
+        slice.set_bits(64, 64, u64::MAX);
+        assert_eq!(u64::MAX, slice.get_bits(64, 64));
+        assert_eq!(0b1111_0001, slice.get_bits(60, 8));
+
+        slice.set_bits(100, 64, 0x0123_4567_89ab_cdef);
+        assert_eq!(0x0123_4567_89ab_cdef, slice.get_bits(100, 64));
+        assert_eq!((1 << 36) - 1, slice.get_bits(64, 36), "neighbouring bits modified");
+        for len in 0..=64 {
+            let mask = if len == 0 { 0 } else { u64::MAX >> (64 - len) };
+            assert_eq!(
+                0x0123_4567_89ab_cdef & mask,
+                slice.get_bits(100, len),
+                "incorrect value for length {len}"
+            );
+        }
+
+        for (i, len) in (0..128).zip((1..=64).cycle()) {
+            let v = 0xdead_beef_dead_beef & (u64::MAX >> (64 - len));
+            slice.set_bits(i, len, v);
+            assert_eq!(v, slice.get_bits(i, len), "incorrect value at {i} with length {len}");
+            for j in 0..len {
+                assert_eq!((v >> j) & 1 == 1, slice.get_bit(i + j), "incorrect bit at {}", i + j);
+            }
+        }
+    }
+
+    #[test]
+    fn word_access_test() {
+        let slice = [1u64, 2, 3];
+        assert_eq!(192, slice.bit_len());
+        assert_eq!(3, slice.word_len());
+        assert_eq!(2, slice.get_word(1));
+
+        let v = vec![4u64, 5];
+        assert_eq!(128, v.bit_len());
+        assert_eq!(5, v.get_word(1));
+    }
+
+    #[test]
+    fn block_types_test() {
+        fn check<B: BlockType>() {
+            let mut blocks = vec![B::zero(); 384 / B::BITS];
+            let mut words = [0u64; 6];
+            for (i, len) in (0..256).zip((1..=64).cycle()) {
+                let v = 0xdead_beef_dead_beef & (u64::MAX >> (64 - len));
+                blocks.set_bits(i, len, v);
+                words.set_bits(i, len, v);
+                assert_eq!(v, blocks.get_bits(i, len), "{} bit blocks", B::BITS);
+                blocks.flip_bit(i + len);
+                words.flip_bit(i + len);
+                blocks.set_bit(i / 2, i % 3 == 0);
+                words.set_bit(i / 2, i % 3 == 0);
+                assert!((0..6).all(|w| blocks.get_word(w) == words[w]), "{} bit blocks", B::BITS);
+            }
+            assert!((0..384).all(|i| blocks.get_bit(i) == words.get_bit(i)));
+            assert_eq!(384, blocks.bit_len());
+            assert_eq!(6, blocks.word_len());
+        }
+
+        check::<u8>();
+        check::<u16>();
+        check::<u32>();
+        check::<u64>();
+        check::<u128>();
+    }
+
+    #[test]
+    fn partial_word_access_test() {
+        let bytes = [0xffu8, 0x01, 0x80];
+        assert_eq!(24, bytes.bit_len());
+        assert_eq!(1, bytes.word_len());
+        assert_eq!(0x80_01ff, bytes.get_word(0));
+
+        let blocks = [u128::MAX - 1, 3];
+        assert_eq!(4, blocks.word_len());
+        assert_eq!(u64::MAX - 1, blocks.get_word(0));
+        assert_eq!(u64::MAX, blocks.get_word(1));
+        assert_eq!(3, blocks.get_word(2));
+        assert_eq!(0, blocks.get_word(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_word_out_of_bounds_test() {
+        let slice = [1u64, 2, 3];
+        slice.get_word(3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_bits_out_of_bounds_test() {
+        let slice = [0u64; 2];
+        slice.get_bits(100, 29);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_bits_too_long_test() {
+        let slice = [0u64; 2];
+        slice.get_bits(0, 65);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_bits_too_large_value_test() {
+        let mut slice = [0u64; 2];
+        slice.set_bits(0, 4, 16);
+    }
+
     #[test]
     #[should_panic]
     fn get_out_of_bounds_test() {
-        let slice = [0b1100_1100_1010_1010usize];
+        let slice = [0b1100_1100_1010_1010u64];
         slice.get_bit(100);
     }
 
     #[test]
     #[should_panic]
     fn set_out_of_bounds_test() {
-        let mut slice = [0b1100_1100_1010_1010usize];
+        let mut slice = [0b1100_1100_1010_1010u64];
         slice.set_bit(100, true);
     }
 
     #[test]
     #[should_panic]
     fn flip_out_of_bounds_test() {
-        let mut slice = [0b1100_1100_1010_1010usize];
+        let mut slice = [0b1100_1100_1010_1010u64];
         slice.flip_bit(100);
     }
 }
\ No newline at end of file
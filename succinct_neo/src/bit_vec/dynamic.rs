@@ -0,0 +1,765 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+use crate::rank_select::{RankSupport, SelectSupport};
+
+use super::{BitGet, BitModify, BitVec, Word, WORD_EXP, WORD_MASK, WORD_SIZE};
+
+/// The number of words in a leaf
+const LEAF_WORDS: usize = 8;
+
+/// The maximum number of bits in a leaf
+const LEAF_BITS: usize = LEAF_WORDS * WORD_SIZE;
+
+/// The maximum number of children of an internal node
+const MAX_CHILDREN: usize = 16;
+
+/// A bit vector supporting insertion and removal of bits at arbitrary positions as well as rank
+/// and select queries, all in logarithmic time.
+///
+/// The bits are stored in the leaves of a B-tree, each containing up to 512 bits. Every node
+/// keeps track of the number of bits and ones in its subtree, so that rank and select queries
+/// can descend directly to the correct leaf. Unlike a [`BitVec`] indexed by a
+/// [`FlatPopcount`](crate::rank_select::FlatPopcount), modifications do not require rebuilding
+/// any index.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::{
+///     bit_vec::{BitGet, BitModify, DynamicBitVec},
+///     rank_select::{RankSupport, SelectSupport}
+/// };
+///
+/// let mut bv = DynamicBitVec::new();
+/// bv.push(true);
+/// bv.push(false);
+/// bv.insert(1, true);
+/// assert_eq!(3, bv.len());
+/// assert!(bv.get_bit(1));
+///
+/// bv.set_bit(2, true);
+/// assert_eq!(Some(2), SelectSupport::<true>::select(&bv, 2));
+///
+/// assert!(bv.remove(0));
+/// assert_eq!(2, bv.rank::<true>(2));
+/// ```
+#[derive(Clone)]
+pub struct DynamicBitVec {
+    root: Node,
+}
+
+#[derive(Clone)]
+enum Node {
+    Leaf(Leaf),
+    Internal(Internal),
+}
+
+#[derive(Clone)]
+struct Leaf {
+    /// The bits of this leaf. Bits past the length are always zero.
+    words: [Word; LEAF_WORDS],
+    len: usize,
+    ones: usize,
+}
+
+#[derive(Clone)]
+struct Internal {
+    children: Vec<Node>,
+    /// The number of bits in this subtree
+    len: usize,
+    /// The number of ones in this subtree
+    ones: usize,
+}
+
+impl DynamicBitVec {
+    /// Creates a new empty dynamic bit vector.
+    pub fn new() -> Self {
+        Self {
+            root: Node::Leaf(Leaf::new()),
+        }
+    }
+
+    /// Gets the number of bits in this bit vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    /// Returns `true` if this bit vector has length 0.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of ones in this bit vector.
+    #[inline]
+    pub fn number_of_ones(&self) -> usize {
+        self.root.ones()
+    }
+
+    /// Inserts a bit at the given index, moving all following bits one position to the back.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index to insert the bit at. This may be equal to the length.
+    /// * `value` - The bit to insert.
+    ///
+    /// # Panics
+    ///
+    /// If the index is greater than the length.
+    pub fn insert(&mut self, index: usize, value: bool) {
+        if index > self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        if let Some(right) = self.root.insert(index, value) {
+            let left = core::mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+            self.root = Node::Internal(Internal::new(vec![left, right]));
+        }
+    }
+
+    /// Removes the bit at the given index, moving all following bits one position to the front.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the bit to remove.
+    ///
+    /// returns: The removed bit.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        let value = self.root.remove(index);
+        // Shrink the tree if the root only has one child left
+        if let Node::Internal(internal) = &mut self.root {
+            if internal.children.len() == 1 {
+                self.root = internal.children.pop().unwrap();
+            }
+        }
+        value
+    }
+
+    /// Appends a bit to the end of this bit vector.
+    #[inline]
+    pub fn push(&mut self, value: bool) {
+        self.insert(self.len(), value)
+    }
+
+    /// Removes the last bit of this bit vector.
+    ///
+    /// returns: The removed bit or `None` if the bit vector is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<bool> {
+        (!self.is_empty()).then(|| self.remove(self.len() - 1))
+    }
+}
+
+impl Leaf {
+    fn new() -> Self {
+        Self {
+            words: [0; LEAF_WORDS],
+            len: 0,
+            ones: 0,
+        }
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> bool {
+        (self.words[index >> WORD_EXP] >> (index & WORD_MASK)) & 1 == 1
+    }
+
+    /// Sets a bit and returns its previous value.
+    #[inline]
+    fn set(&mut self, index: usize, value: bool) -> bool {
+        let old = self.get(index);
+        let word = &mut self.words[index >> WORD_EXP];
+        *word = (*word & !(1 << (index & WORD_MASK))) | ((value as Word) << (index & WORD_MASK));
+        self.ones = self.ones + value as usize - old as usize;
+        old
+    }
+
+    /// Inserts a bit, moving all following bits back. The leaf must not be full.
+    fn insert_bit(&mut self, index: usize, value: bool) {
+        let word = index >> WORD_EXP;
+        let offset = index & WORD_MASK;
+        let low_mask = (1 << offset) - 1;
+        let mut carry = self.words[word] >> (WORD_SIZE - 1);
+        self.words[word] = (self.words[word] & low_mask)
+            | ((self.words[word] & !low_mask) << 1)
+            | ((value as Word) << offset);
+        for w in &mut self.words[word + 1..=self.len >> WORD_EXP] {
+            let next = *w >> (WORD_SIZE - 1);
+            *w = (*w << 1) | carry;
+            carry = next;
+        }
+        self.len += 1;
+        self.ones += value as usize;
+    }
+
+    /// Removes a bit, moving all following bits to the front.
+    fn remove_bit(&mut self, index: usize) -> bool {
+        let value = self.get(index);
+        let word = index >> WORD_EXP;
+        let offset = index & WORD_MASK;
+        let last = (self.len - 1) >> WORD_EXP;
+        let low_mask = (1 << offset) - 1;
+        self.words[word] = (self.words[word] & low_mask) | ((self.words[word] >> 1) & !low_mask);
+        for w in word..last {
+            self.words[w] |= (self.words[w + 1] & 1) << (WORD_SIZE - 1);
+            self.words[w + 1] >>= 1;
+        }
+        self.len -= 1;
+        self.ones -= value as usize;
+        value
+    }
+
+    /// Appends all bits of another leaf. Both leaves must fit into one.
+    fn append(&mut self, other: &Leaf) {
+        for start in (0..other.len).step_by(WORD_SIZE) {
+            let len = WORD_SIZE.min(other.len - start);
+            self.words
+                .set_bits(self.len + start, len, other.words[start >> WORD_EXP]);
+        }
+        self.len += other.len;
+        self.ones += other.ones;
+    }
+
+    /// Splits this leaf at the given index and returns the bits from that index onwards.
+    fn split_off(&mut self, at: usize) -> Leaf {
+        let mut right = Leaf::new();
+        for start in (at..self.len).step_by(WORD_SIZE) {
+            let len = WORD_SIZE.min(self.len - start);
+            let bits = self.words.get_bits(start, len);
+            right.words.set_bits(start - at, len, bits);
+        }
+        right.len = self.len - at;
+
+        // Clear the moved bits
+        let word = at >> WORD_EXP;
+        if at & WORD_MASK == 0 {
+            self.words[word..].fill(0);
+        } else {
+            self.words[word] &= (1 << (at & WORD_MASK)) - 1;
+            self.words[word + 1..].fill(0);
+        }
+        self.len = at;
+        self.ones = self.words.iter().map(|w| w.count_ones() as usize).sum();
+        right.ones = right.words.iter().map(|w| w.count_ones() as usize).sum();
+        right
+    }
+
+    /// Counts the ones before the given index.
+    fn rank(&self, index: usize) -> usize {
+        let word = index >> WORD_EXP;
+        let full = self.words[..word]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>();
+        let rest = index & WORD_MASK;
+        if rest == 0 {
+            full
+        } else {
+            full + (self.words[word] & ((1 << rest) - 1)).count_ones() as usize
+        }
+    }
+
+    /// Finds the one or zero with the given rank, which must exist in this leaf.
+    fn select<const TARGET: bool>(&self, mut rank: usize) -> usize {
+        for (i, &word) in self.words.iter().enumerate() {
+            let mut word = if TARGET { word } else { !word };
+            let count = word.count_ones() as usize;
+            if count > rank {
+                for _ in 0..rank {
+                    word &= word - 1;
+                }
+                return (i << WORD_EXP) + word.trailing_zeros() as usize;
+            }
+            rank -= count;
+        }
+        unreachable!("the bit with the given rank is in this leaf")
+    }
+}
+
+impl Internal {
+    fn new(children: Vec<Node>) -> Self {
+        let len = children.iter().map(Node::len).sum();
+        let ones = children.iter().map(Node::ones).sum();
+        Self {
+            children,
+            len,
+            ones,
+        }
+    }
+
+    /// Finds the child containing the given index.
+    ///
+    /// returns: The index of the child and the index inside the child. If the index is equal to
+    /// the length, this is the end of the last child.
+    fn locate(&self, mut index: usize) -> (usize, usize) {
+        let last = self.children.len() - 1;
+        for (i, child) in self.children[..last].iter().enumerate() {
+            if index < child.len() {
+                return (i, index);
+            }
+            index -= child.len();
+        }
+        (last, index)
+    }
+
+    /// Merges the child at the given index with one of its siblings if it has become too small,
+    /// or moves elements from the sibling to it.
+    fn rebalance(&mut self, child: usize) {
+        if self.children.len() < 2 {
+            return;
+        }
+        let left = if child + 1 < self.children.len() {
+            child
+        } else {
+            child - 1
+        };
+        let (l, r) = self.children.split_at_mut(left + 1);
+        let merged = match (&mut l[left], &mut r[0]) {
+            (Node::Leaf(l), Node::Leaf(r)) => {
+                let total = l.len + r.len;
+                if total <= LEAF_BITS {
+                    l.append(r);
+                    true
+                } else {
+                    let mid = total / 2;
+                    if l.len < mid {
+                        let rest = r.split_off(mid - l.len);
+                        l.append(r);
+                        *r = rest;
+                    } else {
+                        let mut moved = l.split_off(mid);
+                        moved.append(r);
+                        *r = moved;
+                    }
+                    false
+                }
+            }
+            (Node::Internal(l), Node::Internal(r)) => {
+                let total = l.children.len() + r.children.len();
+                if total <= MAX_CHILDREN {
+                    l.children.append(&mut r.children);
+                    l.len += r.len;
+                    l.ones += r.ones;
+                    true
+                } else {
+                    let mut children = core::mem::take(&mut l.children);
+                    children.append(&mut r.children);
+                    let right = children.split_off(total / 2);
+                    *l = Internal::new(children);
+                    *r = Internal::new(right);
+                    false
+                }
+            }
+            _ => unreachable!("all leaves are on the same level"),
+        };
+        if merged {
+            self.children.remove(left + 1);
+        }
+    }
+}
+
+impl Node {
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.len,
+            Node::Internal(internal) => internal.len,
+        }
+    }
+
+    #[inline]
+    fn ones(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.ones,
+            Node::Internal(internal) => internal.ones,
+        }
+    }
+
+    /// Returns `true` if this node should be merged with a sibling.
+    #[inline]
+    fn is_underfull(&self) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.len < LEAF_BITS / 4,
+            Node::Internal(internal) => internal.children.len() < MAX_CHILDREN / 4,
+        }
+    }
+
+    fn get(&self, mut index: usize) -> bool {
+        let mut node = self;
+        loop {
+            match node {
+                Node::Leaf(leaf) => return leaf.get(index),
+                Node::Internal(internal) => {
+                    let (child, child_index) = internal.locate(index);
+                    node = &internal.children[child];
+                    index = child_index;
+                }
+            }
+        }
+    }
+
+    /// Sets a bit and returns its previous value.
+    fn set(&mut self, index: usize, value: bool) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.set(index, value),
+            Node::Internal(internal) => {
+                let (child, child_index) = internal.locate(index);
+                let old = internal.children[child].set(child_index, value);
+                internal.ones = internal.ones + value as usize - old as usize;
+                old
+            }
+        }
+    }
+
+    /// Inserts a bit into this subtree.
+    ///
+    /// returns: A new sibling to the right of this node if this node had to be split.
+    fn insert(&mut self, index: usize, value: bool) -> Option<Node> {
+        match self {
+            Node::Leaf(leaf) => {
+                if leaf.len < LEAF_BITS {
+                    leaf.insert_bit(index, value);
+                    return None;
+                }
+                let mut right = leaf.split_off(LEAF_BITS / 2);
+                if index <= LEAF_BITS / 2 {
+                    leaf.insert_bit(index, value);
+                } else {
+                    right.insert_bit(index - LEAF_BITS / 2, value);
+                }
+                Some(Node::Leaf(right))
+            }
+            Node::Internal(internal) => {
+                let (child, child_index) = internal.locate(index);
+                if let Some(new) = internal.children[child].insert(child_index, value) {
+                    internal.children.insert(child + 1, new);
+                }
+                internal.len += 1;
+                internal.ones += value as usize;
+                if internal.children.len() <= MAX_CHILDREN {
+                    return None;
+                }
+                let right = internal.children.split_off(MAX_CHILDREN / 2);
+                let right = Internal::new(right);
+                internal.len -= right.len;
+                internal.ones -= right.ones;
+                Some(Node::Internal(right))
+            }
+        }
+    }
+
+    /// Removes a bit from this subtree and returns it.
+    fn remove(&mut self, index: usize) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.remove_bit(index),
+            Node::Internal(internal) => {
+                let (child, child_index) = internal.locate(index);
+                let value = internal.children[child].remove(child_index);
+                internal.len -= 1;
+                internal.ones -= value as usize;
+                if internal.children[child].is_underfull() {
+                    internal.rebalance(child);
+                }
+                value
+            }
+        }
+    }
+
+    /// Counts the ones before the given index.
+    fn rank(&self, mut index: usize) -> usize {
+        let mut node = self;
+        let mut ones = 0;
+        loop {
+            match node {
+                Node::Leaf(leaf) => return ones + leaf.rank(index),
+                Node::Internal(internal) => {
+                    let last = internal.children.len() - 1;
+                    node = &internal.children[last];
+                    for child in &internal.children[..last] {
+                        if index < child.len() {
+                            node = child;
+                            break;
+                        }
+                        index -= child.len();
+                        ones += child.ones();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds the one or zero with the given rank, which must exist in this subtree.
+    fn select<const TARGET: bool>(&self, mut rank: usize) -> usize {
+        let mut node = self;
+        let mut position = 0;
+        loop {
+            match node {
+                Node::Leaf(leaf) => return position + leaf.select::<TARGET>(rank),
+                Node::Internal(internal) => {
+                    for child in &internal.children {
+                        let count = if TARGET {
+                            child.ones()
+                        } else {
+                            child.len() - child.ones()
+                        };
+                        if rank < count {
+                            node = child;
+                            break;
+                        }
+                        rank -= count;
+                        position += child.len();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for DynamicBitVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitGet for DynamicBitVec {
+    #[inline]
+    unsafe fn get_bit_unchecked(&self, index: usize) -> bool {
+        self.root.get(index)
+    }
+
+    fn get_bit(&self, index: usize) -> bool {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        self.root.get(index)
+    }
+}
+
+impl BitModify for DynamicBitVec {
+    #[inline]
+    unsafe fn set_bit_unchecked(&mut self, index: usize, value: bool) {
+        self.root.set(index, value);
+    }
+
+    fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        self.root.set(index, value);
+    }
+
+    #[inline]
+    unsafe fn flip_bit_unchecked(&mut self, index: usize) {
+        let value = self.root.get(index);
+        self.root.set(index, !value);
+    }
+
+    fn flip_bit(&mut self, index: usize) {
+        let value = self.get_bit(index);
+        self.root.set(index, !value);
+    }
+}
+
+impl RankSupport for DynamicBitVec {
+    fn rank<const TARGET: bool>(&self, index: usize) -> usize {
+        if index > self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        let ones = self.root.rank(index);
+        if TARGET {
+            ones
+        } else {
+            index - ones
+        }
+    }
+}
+
+impl SelectSupport<true> for DynamicBitVec {
+    fn select(&self, rank: usize) -> Option<usize> {
+        (rank < self.number_of_ones()).then(|| self.root.select::<true>(rank))
+    }
+}
+
+impl SelectSupport<false> for DynamicBitVec {
+    fn select(&self, rank: usize) -> Option<usize> {
+        (rank < self.len() - self.number_of_ones()).then(|| self.root.select::<false>(rank))
+    }
+}
+
+impl From<&BitVec> for DynamicBitVec {
+    fn from(bv: &BitVec) -> Self {
+        bv.iter().collect()
+    }
+}
+
+impl Extend<bool> for DynamicBitVec {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|value| self.push(value))
+    }
+}
+
+impl FromIterator<bool> for DynamicBitVec {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut bv = Self::new();
+        bv.extend(iter);
+        bv
+    }
+}
+
+impl Debug for DynamicBitVec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[")?;
+        for i in 0..self.len() {
+            write!(f, "{}", self.root.get(i) as u8)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bit_vec::{BitGet, BitModify, BitVec};
+    use crate::rank_select::{RankSupport, SelectSupport};
+    use crate::test_util::Rng;
+
+    use super::{DynamicBitVec, Node};
+
+    /// Checks the contents of the dynamic bit vector against a vector of bools and checks that
+    /// the cached counts are correct.
+    fn check(bv: &DynamicBitVec, expected: &[bool]) {
+        assert_eq!(expected.len(), bv.len(), "length incorrect");
+        let (mut ones, mut zeros) = (0, 0);
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(value, bv.get_bit(i), "bit {i} incorrect");
+            assert_eq!(ones, bv.rank::<true>(i), "rank of ones at {i}");
+            assert_eq!(zeros, bv.rank::<false>(i), "rank of zeros at {i}");
+            if value {
+                assert_eq!(
+                    Some(i),
+                    SelectSupport::<true>::select(bv, ones),
+                    "{ones}th one"
+                );
+                ones += 1;
+            } else {
+                assert_eq!(
+                    Some(i),
+                    SelectSupport::<false>::select(bv, zeros),
+                    "{zeros}th zero"
+                );
+                zeros += 1;
+            }
+        }
+        assert_eq!(ones, bv.number_of_ones());
+        assert_eq!(ones, bv.rank::<true>(bv.len()));
+        assert_eq!(None, SelectSupport::<true>::select(bv, ones));
+        assert_eq!(None, SelectSupport::<false>::select(bv, zeros));
+        check_node(&bv.root);
+    }
+
+    fn check_node(node: &Node) -> (usize, usize) {
+        match node {
+            Node::Leaf(leaf) => {
+                let ones: usize = leaf.words.iter().map(|w| w.count_ones() as usize).sum();
+                assert_eq!(ones, leaf.ones, "leaf ones incorrect");
+                (leaf.len, leaf.ones)
+            }
+            Node::Internal(internal) => {
+                let (len, ones) = internal
+                    .children
+                    .iter()
+                    .map(check_node)
+                    .fold((0, 0), |(l, o), (cl, co)| (l + cl, o + co));
+                assert_eq!(len, internal.len, "internal length incorrect");
+                assert_eq!(ones, internal.ones, "internal ones incorrect");
+                (len, ones)
+            }
+        }
+    }
+
+    #[test]
+    fn push_pop_test() {
+        let mut bv = DynamicBitVec::new();
+        let mut expected = Vec::new();
+        for i in 0..5000 {
+            bv.push(i % 3 == 0);
+            expected.push(i % 3 == 0);
+        }
+        check(&bv, &expected);
+        for _ in 0..4000 {
+            assert_eq!(expected.pop(), bv.pop());
+        }
+        check(&bv, &expected);
+        while bv.pop().is_some() {}
+        assert!(bv.is_empty());
+        assert!(matches!(bv.root, Node::Leaf(_)));
+    }
+
+    #[test]
+    fn random_operations_test() {
+        let mut bv = DynamicBitVec::new();
+        let mut expected = Vec::new();
+        let mut rng = Rng::new();
+        for round in 0..60000 {
+            let r = rng.next_word() as usize;
+            // Grow in the first half and shrink in the second half
+            let insert = if round < 30000 {
+                !r.is_multiple_of(4)
+            } else {
+                r.is_multiple_of(4)
+            };
+            if insert || expected.is_empty() {
+                let index = (r >> 8) % (expected.len() + 1);
+                let value = (r >> 4) & 1 == 1;
+                bv.insert(index, value);
+                expected.insert(index, value);
+            } else if r.is_multiple_of(3) {
+                let index = (r >> 8) % expected.len();
+                let value = (r >> 4) & 1 == 1;
+                bv.set_bit(index, value);
+                expected[index] = value;
+            } else {
+                let index = (r >> 8) % expected.len();
+                assert_eq!(expected.remove(index), bv.remove(index));
+            }
+            if round % 10000 == 0 {
+                check(&bv, &expected);
+            }
+        }
+        check(&bv, &expected);
+    }
+
+    #[test]
+    fn from_bit_vec_test() {
+        let bv: BitVec = (0..3000).map(|i| i % 7 < 2).collect();
+        let mut dynamic = DynamicBitVec::from(&bv);
+        let mut expected = (0..3000).map(|i| i % 7 < 2).collect::<Vec<_>>();
+        check(&dynamic, &expected);
+
+        dynamic.flip_bit(10);
+        expected[10] = !expected[10];
+        check(&dynamic, &expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds_test() {
+        let mut bv = DynamicBitVec::new();
+        bv.insert(1, true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds_test() {
+        let mut bv: DynamicBitVec = [true, false].into_iter().collect();
+        bv.remove(2);
+    }
+}
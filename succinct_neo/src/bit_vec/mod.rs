@@ -1,30 +1,52 @@
-use std::fmt::{Debug, Formatter};
-use std::ops::{Deref, DerefMut};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
 
 use itertools::Itertools;
 
 pub use crate::bit_vec::slice::BitSlice;
+pub use dynamic::DynamicBitVec;
 pub use traits::*;
+pub use crate::traits::BlockType;
 
 use self::slice::Iter;
+#[cfg(feature = "std")]
+use crate::serialize::{self, SerializeError};
 
 /// Trait implementations for the backing type of BitVec
 mod backing;
+/// Bit vectors supporting insertion and removal of bits
+pub mod dynamic;
+/// Bitwise operations between bit vectors and bit slices
+mod ops;
 /// Bit slices offering views into types that offer bit access
 pub mod slice;
 pub mod traits;
 
-/// The word size on this machine in bits
-const WORD_SIZE: usize = 64;
+/// The type of the words the bits are stored in.
+///
+/// This is `u64` on all targets independent of the pointer width, so bit vectors and the indices
+/// built on top of them have the same layout and serialized format on 32 and 64 bit platforms.
+pub type Word = u64;
+
+/// The word size in bits
+pub(crate) const WORD_SIZE: usize = Word::BITS as usize;
 
 /// The logarithm of the word size for multiplying/dividing by the word size quickly
-const WORD_EXP: usize = 6;
+pub(crate) const WORD_EXP: usize = 6;
 
 /// A mask for quickly calculating the modulus
-const WORD_MASK: usize = (1 << WORD_EXP) - 1;
+pub(crate) const WORD_MASK: usize = (1 << WORD_EXP) - 1;
+
+/// The magic number identifying a serialized [`BitVec`]
+const BIT_VEC_MAGIC: [u8; 4] = *b"SNBV";
 
 ///
-/// A fixed-size bit vector allocated on the heap.
+/// A growable bit vector allocated on the heap.
 ///
 /// # Examples
 ///
@@ -44,16 +66,37 @@ const WORD_MASK: usize = (1 << WORD_EXP) - 1;
 /// for i in 0..bv.len() {
 ///     assert_eq!(4 <= i && i < 8, bv.get_bit(i))
 /// }
+///
+/// // Bits can be added to and removed from the end of the bit vector
+/// bv.push(true);
+/// assert_eq!(17, bv.len());
+/// assert_eq!(Some(true), bv.pop());
+/// ```
+///
+/// The bits are stored in blocks of type `B`, which are [`Word`]s by default. Using a smaller
+/// [`BlockType`] such as `u8` allows sharing the blocks with byte-oriented buffers without
+/// converting them. The first bit is always the least significant bit of the first block.
+///
 /// ```
+/// use succinct_neo::bit_vec::{BitGet, BitVec};
 ///
+/// let mut bv = BitVec::<u8>::from_blocks(vec![0b1000_0001, 0b1111_0010], 12);
+/// assert!(bv.get_bit(7));
+/// assert!(bv.get_bit(9));
+///
+/// // Bits past the end are cleared
+/// bv.push(true);
+/// assert_eq!(&[0b1000_0001, 0b0001_0010], bv.raw());
+/// ```
 #[derive(Clone)]
-pub struct BitVec {
-    data: BitSlice<Box<[usize]>>,
+pub struct BitVec<B = Word> {
+    data: BitSlice<Vec<B>>,
     size: usize,
 }
 
 impl BitVec {
-    /// Creates a new [`BitVec`].
+    /// Creates a new [`BitVec`] storing its bits in [`Word`]s. See [`BitVec::zeroed`] for other
+    /// block types.
     ///
     /// # Arguments
     ///
@@ -70,20 +113,302 @@ impl BitVec {
     /// let bv = BitVec::new(16);
     /// ```
     pub fn new(size: usize) -> Self {
-        let v = vec![0usize; (size as f64 / WORD_SIZE as f64).ceil() as usize];
-        let b = v.into_boxed_slice();
+        Self::zeroed(size)
+    }
+
+    /// Creates a new empty [`BitVec`] with space for at least `capacity` bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The number of bits to allocate space for.
+    ///
+    /// returns: A new bit vector of length 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let bv = BitVec::with_capacity(100);
+    /// assert!(bv.is_empty());
+    /// assert!(bv.capacity() >= 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let v = Vec::with_capacity(capacity.div_ceil(WORD_SIZE));
         Self {
-            data: BitSlice::new(b, 0, size),
+            data: BitSlice::new(v, 0, 0),
+            size: 0,
+        }
+    }
+
+    /// Writes this bit vector in a binary format which can be read with [`BitVec::read_from`].
+    ///
+    /// The format consists of a header, the length in bits and the words of the bit vector, all
+    /// stored in little-endian byte order. See the [`serialize`] module for
+    /// details.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the bit vector to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitModify, BitVec};
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.set_bit(42, true);
+    ///
+    /// let mut buf = Vec::new();
+    /// bv.write_to(&mut buf).unwrap();
+    ///
+    /// let read = BitVec::read_from(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(bv, read);
+    /// assert!(read.get_bit(42));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        serialize::write_header(writer, BIT_VEC_MAGIC)?;
+        serialize::write_u64(writer, self.size as u64)?;
+        serialize::write_words(writer, self.raw())
+    }
+
+    /// Reads a bit vector written by [`BitVec::write_to`].
+    ///
+    /// # Arguments
+    ///
+    /// * `reader`: The reader to read the bit vector from.
+    ///
+    /// returns: The bit vector or an error if reading failed or the data is malformed.
+    #[cfg(feature = "std")]
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, SerializeError> {
+        serialize::read_header(reader, BIT_VEC_MAGIC)?;
+        let size = serialize::read_usize(reader)?;
+        let words = serialize::read_words(reader, size.div_ceil(WORD_SIZE))?;
+        let rest_bits = size & WORD_MASK;
+        if rest_bits != 0 && words[words.len() - 1] >> rest_bits != 0 {
+            return Err(SerializeError::Invalid("bits past the end of the bit vector are set"));
+        }
+        Ok(Self {
+            data: BitSlice::new(words, 0, size),
             size,
+        })
+    }
+}
+
+impl<B: BlockType> BitVec<B> {
+    /// Creates a new bit vector of the given length with all bits set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `size`: The size of this bitvector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitVec};
+    ///
+    /// let bv = BitVec::<u8>::zeroed(20);
+    /// assert_eq!(20, bv.len());
+    /// assert_eq!(3, bv.raw().len());
+    /// ```
+    pub fn zeroed(size: usize) -> Self {
+        let v = vec![B::zero(); size.div_ceil(B::BITS)];
+        Self {
+            data: BitSlice::new(v, 0, size),
+            size,
+        }
+    }
+
+    /// Creates a bit vector from the given blocks without copying them.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks`: The blocks containing the bits.
+    /// * `size`: The number of bits in the bit vector. Blocks which are not needed for this many
+    ///   bits are removed and the bits past the end are set to 0.
+    ///
+    /// # Panics
+    ///
+    /// If the blocks contain less than `size` bits.
+    pub fn from_blocks(mut blocks: Vec<B>, size: usize) -> Self {
+        if size > blocks.len() * B::BITS {
+            panic!("size is {size} but the blocks contain {} bits", blocks.len() * B::BITS)
         }
+        blocks.truncate(size.div_ceil(B::BITS));
+        let mut bv = Self {
+            data: BitSlice::new(blocks, 0, size),
+            size,
+        };
+        bv.clear_unused_bits();
+        bv
     }
 
-    pub fn raw(&self) -> &[usize] {
+    /// Consumes this bit vector and returns the blocks backing it. Bits beyond the length of the
+    /// bit vector are always zero.
+    pub fn into_blocks(self) -> Vec<B> {
+        self.data.into_backing()
+    }
+
+    /// Gets the raw blocks backing this bit vector. Bits beyond the length of the bit vector are
+    /// always zero.
+    pub fn raw(&self) -> &[B] {
         self.data.backing()
     }
+
+    /// The number of bits this bit vector can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.backing().capacity() * B::BITS
+    }
+
+    /// Reserves space for at least `additional` more bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `additional`: The number of bits to reserve space for in addition to the current length.
+    pub fn reserve(&mut self, additional: usize) {
+        let required_blocks = (self.size + additional).div_ceil(B::BITS);
+        let backing = self.data.backing_mut();
+        backing.reserve(required_blocks.saturating_sub(backing.len()));
+    }
+
+    /// Appends a bit to the end of this bit vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value of the bit to append.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitVec};
+    ///
+    /// let mut bv = BitVec::new(0);
+    /// bv.push(true);
+    /// bv.push(false);
+    ///
+    /// assert_eq!(2, bv.len());
+    /// assert_eq!(true, bv.get_bit(0));
+    /// assert_eq!(false, bv.get_bit(1));
+    /// ```
+    pub fn push(&mut self, value: bool) {
+        // All blocks are full, so we need a new one
+        if self.size.is_multiple_of(B::BITS) {
+            self.data.backing_mut().push(B::zero());
+        }
+        self.size += 1;
+        self.data.set_len(self.size);
+        // SAFETY: We just made space for this bit
+        unsafe { self.data.set_bit_unchecked(self.size - 1, value) }
+    }
+
+    /// Removes the last bit of this bit vector and returns it.
+    ///
+    /// returns: The last bit or `None` if the bit vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let mut bv = BitVec::new(0);
+    /// bv.push(true);
+    /// bv.push(false);
+    ///
+    /// assert_eq!(Some(false), bv.pop());
+    /// assert_eq!(Some(true), bv.pop());
+    /// assert_eq!(None, bv.pop());
+    /// ```
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: The bit vector is not empty
+        let value = unsafe { self.data.get_bit_unchecked(self.size - 1) };
+        self.truncate(self.size - 1);
+        Some(value)
+    }
+
+    /// Shortens this bit vector to the given length. If `len` is greater or equal to the current
+    /// length, this has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The new length of the bit vector.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.size {
+            return;
+        }
+        self.data.backing_mut().truncate(len.div_ceil(B::BITS));
+        self.size = len;
+        self.data.set_len(len);
+        self.clear_unused_bits();
+    }
+
+    /// Sets all bits in the last block which are not part of the bit vector to zero.
+    #[inline]
+    fn clear_unused_bits(&mut self) {
+        let rest_bits = self.size % B::BITS;
+        if rest_bits != 0 {
+            if let Some(last) = self.data.backing_mut().last_mut() {
+                *last &= (B::one() << rest_bits) - B::one();
+            }
+        }
+    }
+
+    /// Removes all bits from this bit vector.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Resizes this bit vector to the given length. If the bit vector grows, the new bits are set
+    /// to `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `len`: The new length of the bit vector.
+    /// * `value`: The value of the newly added bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitVec};
+    ///
+    /// let mut bv = BitVec::new(10);
+    /// bv.resize(100, true);
+    ///
+    /// assert_eq!(100, bv.len());
+    /// assert_eq!(false, bv.get_bit(9));
+    /// assert_eq!(true, bv.get_bit(10));
+    ///
+    /// bv.resize(5, true);
+    /// assert_eq!(5, bv.len());
+    /// ```
+    pub fn resize(&mut self, len: usize, value: bool) {
+        if len <= self.size {
+            self.truncate(len);
+            return;
+        }
+        let old_size = self.size;
+        let backing = self.data.backing_mut();
+        let fill = if value { B::max_value() } else { B::zero() };
+        backing.resize(len.div_ceil(B::BITS), fill);
+        if value {
+            // Fill the rest of the previously last block
+            let rest_bits = old_size % B::BITS;
+            if rest_bits != 0 {
+                backing[old_size / B::BITS] |= !((B::one() << rest_bits) - B::one());
+            }
+        }
+        self.size = len;
+        self.data.set_len(len);
+        self.clear_unused_bits();
+    }
 }
 
-impl BitModify for BitVec {
+impl<B: BlockType> BitModify for BitVec<B> {
     #[inline]
     unsafe fn set_bit_unchecked(&mut self, index: usize, value: bool) {
         self.data.set_bit_unchecked(index, value)
@@ -109,21 +434,79 @@ impl BitModify for BitVec {
         }
         unsafe { self.flip_bit_unchecked(index) }
     }
+
+    #[inline]
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        self.data.set_bits_unchecked(index, len, value)
+    }
+
+    #[inline]
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        self.data.set_bits(index, len, value)
+    }
 }
 
-impl<'a> IntoIterator for &'a BitVec {
+impl<B: BlockType> WordAccess for BitVec<B> {
+    #[inline]
+    fn bit_len(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn word_len(&self) -> usize {
+        self.size.div_ceil(WORD_SIZE)
+    }
+
+    #[inline]
+    unsafe fn get_word_unchecked(&self, index: usize) -> Word {
+        // Bits past the end are always zero
+        self.raw().get_word_unchecked(index)
+    }
+}
+
+impl<Backing: BitGet> From<&BitSlice<Backing>> for BitVec {
+    fn from(slice: &BitSlice<Backing>) -> Self {
+        let mut bv = BitVec::new(slice.len());
+        let words = bv.data.backing_mut();
+        for (word, i) in words.iter_mut().zip((0..slice.len()).step_by(WORD_SIZE)) {
+            // SAFETY: The range is in bounds of the slice
+            *word = unsafe { slice.get_bits_unchecked(i, WORD_SIZE.min(slice.len() - i)) };
+        }
+        bv
+    }
+}
+
+impl<B: BlockType> Extend<bool> for BitVec<B> {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<B: BlockType> FromIterator<bool> for BitVec<B> {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut bv = Self::default();
+        bv.extend(iter);
+        bv
+    }
+}
+
+impl<'a, B: BlockType> IntoIterator for &'a BitVec<B> {
     type Item = bool;
 
-    type IntoIter = Iter<&'a Box<[usize]>>;
+    type IntoIter = Iter<&'a Vec<B>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.data.iter()
     }
 }
 
-impl Debug for BitVec {
+impl<B: BlockType> Debug for BitVec<B> {
     #[allow(unstable_name_collisions)]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{{")
             .and_then(|_| {
                 write!(
@@ -139,34 +522,49 @@ impl Debug for BitVec {
     }
 }
 
-impl Deref for BitVec {
-    type Target = BitSlice<Box<[usize]>>;
+impl<B: BlockType> Default for BitVec<B> {
+    fn default() -> Self {
+        Self::zeroed(0)
+    }
+}
+
+impl<B: BlockType> PartialEq for BitVec<B> {
+    fn eq(&self, other: &Self) -> bool {
+        // Bits past the end are always zero, so we can compare the blocks directly
+        self.size == other.size && self.raw() == other.raw()
+    }
+}
+
+impl<B: BlockType> Eq for BitVec<B> {}
+
+impl<B> Deref for BitVec<B> {
+    type Target = BitSlice<Vec<B>>;
 
     fn deref(&self) -> &Self::Target {
         &self.data
     }
 }
 
-impl DerefMut for BitVec {
+impl<B> DerefMut for BitVec<B> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.data
     }
 }
 
-impl AsRef<BitSlice<Box<[usize]>>> for BitVec {
-    fn as_ref(&self) -> &BitSlice<Box<[usize]>> {
+impl<B> AsRef<BitSlice<Vec<B>>> for BitVec<B> {
+    fn as_ref(&self) -> &BitSlice<Vec<B>> {
         &self.data
     }
 }
 
-impl AsRef<[usize]> for BitVec {
-    fn as_ref(&self) -> &[usize] {
+impl<B> AsRef<[B]> for BitVec<B> {
+    fn as_ref(&self) -> &[B] {
         self.data.backing()
     }
 }
 
-impl AsMut<BitSlice<Box<[usize]>>> for BitVec {
-    fn as_mut(&mut self) -> &mut BitSlice<Box<[usize]>> {
+impl<B> AsMut<BitSlice<Vec<B>>> for BitVec<B> {
+    fn as_mut(&mut self) -> &mut BitSlice<Vec<B>> {
         &mut self.data
     }
 }
@@ -176,8 +574,10 @@ mod test {
     use crate::bit_vec::BitGet;
     use crate::bit_vec::slice::BitSlice;
 
-    use super::BitVec;
-    use super::traits::BitModify;
+    use super::{BitVec, Word};
+    use super::traits::{BitModify, WordAccess};
+    #[cfg(feature = "std")]
+    use crate::serialize::SerializeError;
 
     #[test]
     fn basics_test() {
@@ -192,7 +592,7 @@ mod test {
         bv.set(10, true);
 
         assert_eq!(bv.backing(), AsRef::<BitSlice<_>>::as_ref(&bv).backing());
-        assert_eq!(bv.raw(), AsRef::<[usize]>::as_ref(&bv));
+        assert_eq!(bv.raw(), AsRef::<[Word]>::as_ref(&bv));
         assert_eq!(bv.backing(), bv.clone().as_mut().backing());
 
         println!("{bv:?}")
@@ -254,6 +654,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn bits_test() {
+        let mut bv = BitVec::new(160);
+        bv.set_bits(40, 64, 0x0123_4567_89ab_cdef);
+        assert_eq!(0x0123_4567_89ab_cdef, bv.get_bits(40, 64));
+        assert_eq!(0xab_cdef << 40, bv.raw()[0]);
+        assert_eq!(0x01_2345_6789, bv.raw()[1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_bits_out_of_bounds_test() {
+        let mut bv = BitVec::new(100);
+        bv.set_bits(90, 11, 0);
+    }
+
     #[test]
     fn into_iter_test() {
         let mut bv = BitVec::new(160);
@@ -274,6 +690,176 @@ mod test {
         }
     }
 
+    #[test]
+    fn push_pop_test() {
+        let mut bv = BitVec::with_capacity(10);
+        assert!(bv.capacity() >= 10, "capacity too small");
+        for i in 0..200 {
+            bv.push(i % 3 == 0);
+        }
+        assert_eq!(200, bv.len(), "length incorrect");
+        assert_eq!(4, bv.raw().len(), "number of words incorrect");
+        for i in 0..bv.len() {
+            assert_eq!(i % 3 == 0, bv.get_bit(i), "incorrect value at index {i}");
+        }
+
+        for i in (0..200).rev() {
+            assert_eq!(Some(i % 3 == 0), bv.pop(), "incorrect value popped at index {i}");
+            assert_eq!(i, bv.len(), "length incorrect");
+            assert_eq!(i.div_ceil(64), bv.raw().len(), "number of words incorrect");
+        }
+        assert_eq!(None, bv.pop());
+    }
+
+    #[test]
+    fn truncate_test() {
+        let mut bv = BitVec::new(0);
+        bv.resize(150, true);
+        bv.truncate(200);
+        assert_eq!(150, bv.len(), "truncating to a larger length changed the length");
+
+        bv.truncate(70);
+        assert_eq!(70, bv.len(), "length incorrect");
+        assert_eq!(&[Word::MAX, (1 << 6) - 1], bv.raw(), "bits past the end not cleared");
+
+        // Newly pushed bits must not contain any old data
+        bv.push(false);
+        assert!(!bv.get_bit(70));
+
+        bv.clear();
+        assert!(bv.is_empty());
+        assert!(bv.raw().is_empty());
+    }
+
+    #[test]
+    fn resize_test() {
+        let mut bv = BitVec::new(10);
+        bv.set_bit(3, true);
+
+        bv.resize(100, true);
+        assert_eq!(100, bv.len(), "length incorrect");
+        for i in 0..bv.len() {
+            assert_eq!(i == 3 || i >= 10, bv.get_bit(i), "incorrect value at index {i}");
+        }
+        assert_eq!((1 << 36) - 1, bv.raw()[1], "bits past the end not cleared");
+
+        bv.resize(130, false);
+        assert_eq!(130, bv.len(), "length incorrect");
+        for i in 0..bv.len() {
+            let expected = i == 3 || (10..100).contains(&i);
+            assert_eq!(expected, bv.get_bit(i), "incorrect value at index {i}");
+        }
+
+        bv.resize(5, true);
+        assert_eq!(5, bv.len(), "length incorrect");
+        assert_eq!(&[0b1000], bv.raw());
+    }
+
+    #[test]
+    fn extend_test() {
+        let mut bv: BitVec = (0..100).map(|i| i % 5 == 0).collect();
+        assert_eq!(100, bv.len(), "length incorrect");
+        bv.extend((0..100).map(|i| i % 7 == 0));
+        assert_eq!(200, bv.len(), "length incorrect");
+        for i in 0..bv.len() {
+            let expected = if i < 100 { i % 5 == 0 } else { (i - 100) % 7 == 0 };
+            assert_eq!(expected, bv.get_bit(i), "incorrect value at index {i}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn serialize_test() {
+        for len in [0usize, 1, 64, 100, 1000] {
+            let bv: BitVec = (0..len).map(|i| i % 3 == 0).collect();
+            let mut buf = Vec::new();
+            bv.write_to(&mut buf).unwrap();
+            assert_eq!(16 + 8 * len.div_ceil(64), buf.len(), "size incorrect for {len} bits");
+            assert_eq!(bv, BitVec::read_from(&mut buf.as_slice()).unwrap());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deserialize_invalid_test() {
+        let bv: BitVec = (0..100).map(|i| i % 3 == 0).collect();
+        let mut buf = Vec::new();
+        bv.write_to(&mut buf).unwrap();
+
+        // Truncated input
+        let res = BitVec::read_from(&mut &buf[..buf.len() - 1]);
+        assert!(matches!(res, Err(SerializeError::Io(_))));
+
+        // A bit past the end is set
+        let mut corrupted = buf.clone();
+        *corrupted.last_mut().unwrap() = 0x80;
+        let res = BitVec::read_from(&mut corrupted.as_slice());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // A huge length must not be allocated up front
+        let mut corrupted = buf.clone();
+        corrupted[8..16].copy_from_slice(&(usize::MAX as u64).to_le_bytes());
+        let res = BitVec::read_from(&mut corrupted.as_slice());
+        assert!(matches!(res, Err(SerializeError::Io(_))));
+
+        let res = BitVec::read_from(&mut &b"SNIV\x01\0\0\0"[..]);
+        assert!(matches!(res, Err(SerializeError::InvalidMagic { .. })));
+    }
+
+    #[test]
+    fn block_types_test() {
+        fn check<B: super::BlockType>() {
+            let pattern = |i: usize| (i * i + i / 7) % 5 < 2;
+            let mut bv = BitVec::<B>::zeroed(0);
+            let mut expected = BitVec::new(0);
+            for i in 0..500 {
+                bv.push(pattern(i));
+                expected.push(pattern(i));
+            }
+            assert!(bv.iter().eq(expected.iter()), "{} bit blocks", B::BITS);
+            assert_eq!(expected.word_len(), bv.word_len());
+            assert!((0..bv.word_len()).all(|i| bv.get_word(i) == expected.get_word(i)));
+            assert_eq!(expected.count_ones(), bv.count_ones());
+
+            bv.resize(700, true);
+            expected.resize(700, true);
+            bv.truncate(650);
+            expected.truncate(650);
+            bv.set_bits(300, 64, 0x0123_4567_89ab_cdef);
+            expected.set_bits(300, 64, 0x0123_4567_89ab_cdef);
+            assert!(bv.iter().eq(expected.iter()), "{} bit blocks", B::BITS);
+
+            let not = !&bv;
+            assert!(not.iter().eq((!&expected).iter()));
+            assert_eq!(0, (&bv & &not).count_ones());
+            assert_eq!(bv.len(), (bv.clone() | &not).count_ones());
+
+            let blocks = bv.clone().into_blocks();
+            assert_eq!(650usize.div_ceil(B::BITS), blocks.len());
+            assert_eq!(bv, BitVec::from_blocks(blocks, 650));
+        }
+
+        check::<u8>();
+        check::<u16>();
+        check::<u32>();
+        check::<u64>();
+        check::<u128>();
+    }
+
+    #[test]
+    fn from_blocks_test() {
+        let bv = BitVec::<u8>::from_blocks(vec![0xff, 0xff, 0xff], 12);
+        assert_eq!(12, bv.len());
+        assert_eq!(&[0xff, 0x0f], bv.raw());
+        assert_eq!(0x0fff, bv.get_word(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_blocks_too_short_test() {
+        BitVec::<u16>::from_blocks(vec![0, 0], 33);
+    }
+
     #[test]
     #[should_panic]
     fn get_out_of_bounds_mut_test() {
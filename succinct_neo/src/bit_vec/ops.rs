@@ -0,0 +1,365 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::{BitAccess, BitGet, BitModify, BitSlice, BitVec, BlockType, Word, WORD_SIZE};
+use crate::popcount;
+
+/// Panics if the two operands of a bitwise operation do not have the same length.
+#[inline]
+fn check_lengths(lhs: usize, rhs: usize) {
+    if lhs != rhs {
+        panic!("length of left operand is {lhs} but length of right operand is {rhs}")
+    }
+}
+
+/// Combines two bit slices of equal length a word at a time, storing the result in the left one.
+fn assign_op<B1: BitAccess, B2: BitGet>(
+    lhs: &mut BitSlice<B1>,
+    rhs: &BitSlice<B2>,
+    op: impl Fn(Word, Word) -> Word,
+) {
+    check_lengths(lhs.len(), rhs.len());
+    for i in (0..lhs.len()).step_by(WORD_SIZE) {
+        let len = WORD_SIZE.min(lhs.len() - i);
+        // SAFETY: Both slices have the same length and the range is in bounds
+        unsafe {
+            let value = op(lhs.get_bits_unchecked(i, len), rhs.get_bits_unchecked(i, len));
+            lhs.set_bits_unchecked(i, len, value);
+        }
+    }
+}
+
+/// Combines two bit vectors of equal length a block at a time, storing the result in the left one.
+fn assign_op_aligned<B: BlockType>(lhs: &mut BitVec<B>, rhs: &BitVec<B>, op: impl Fn(B, B) -> B) {
+    check_lengths(lhs.len(), rhs.len());
+    for (l, &r) in lhs.data.backing_mut().iter_mut().zip(rhs.raw()) {
+        *l = op(*l, r);
+    }
+    lhs.clear_unused_bits();
+}
+
+/// Combines two bit slices of equal length a word at a time and counts the ones in the result.
+fn fused_count<B1: BitGet, B2: BitGet>(
+    lhs: &BitSlice<B1>,
+    rhs: &BitSlice<B2>,
+    op: impl Fn(Word, Word) -> Word,
+) -> usize {
+    check_lengths(lhs.len(), rhs.len());
+    popcount::count_ones_iter((0..lhs.len()).step_by(WORD_SIZE).map(|i| {
+        let len = WORD_SIZE.min(lhs.len() - i);
+        // SAFETY: Both slices have the same length and the range is in bounds
+        unsafe { op(lhs.get_bits_unchecked(i, len), rhs.get_bits_unchecked(i, len)) }
+    }))
+}
+
+impl<Backing: BitGet> BitSlice<Backing> {
+    /// Counts the number of ones in this slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitModify, BitVec};
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.set_bit(10, true);
+    /// bv.set_bit(70, true);
+    ///
+    /// assert_eq!(2, bv.count_ones());
+    /// assert_eq!(1, bv.slice(50..).count_ones());
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        popcount::count_ones_iter(
+            (0..self.len())
+                .step_by(WORD_SIZE)
+                // SAFETY: The range is in bounds
+                .map(|i| unsafe { self.get_bits_unchecked(i, WORD_SIZE.min(self.len() - i)) }),
+        )
+    }
+
+    /// Counts the number of zeroes in this slice.
+    #[inline]
+    pub fn count_zeros(&self) -> usize {
+        self.len() - self.count_ones()
+    }
+
+    /// Counts the number of ones in the bitwise AND of this slice and another slice of the same
+    /// length without creating the intermediate result.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The slice to combine this slice with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let a: BitVec = (0..100).map(|i| i % 2 == 0).collect();
+    /// let b: BitVec = (0..100).map(|i| i % 3 == 0).collect();
+    ///
+    /// assert_eq!(17, a.and_count(&b));
+    /// assert_eq!(67, a.or_count(&b));
+    /// assert_eq!(50, a.xor_count(&b));
+    /// ```
+    pub fn and_count<B2: BitGet>(&self, other: &BitSlice<B2>) -> usize {
+        fused_count(self, other, |l, r| l & r)
+    }
+
+    /// Counts the number of ones in the bitwise OR of this slice and another slice of the same
+    /// length without creating the intermediate result.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The slice to combine this slice with.
+    pub fn or_count<B2: BitGet>(&self, other: &BitSlice<B2>) -> usize {
+        fused_count(self, other, |l, r| l | r)
+    }
+
+    /// Counts the number of ones in the bitwise XOR of this slice and another slice of the same
+    /// length without creating the intermediate result.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The slice to combine this slice with.
+    pub fn xor_count<B2: BitGet>(&self, other: &BitSlice<B2>) -> usize {
+        fused_count(self, other, |l, r| l ^ r)
+    }
+}
+
+/// Implements a bitwise binary operator and its assigning variant for all combinations of bit
+/// vectors and bit slices.
+macro_rules! bit_op {
+    {$op_trait:ident, $op_fn:ident, $assign_trait:ident, $assign_fn:ident, |$l:ident, $r:ident| $op:expr} => {
+        impl<B1: BitAccess, B2: BitGet> $assign_trait<&BitSlice<B2>> for BitSlice<B1> {
+            fn $assign_fn(&mut self, rhs: &BitSlice<B2>) {
+                assign_op(self, rhs, |$l, $r| $op)
+            }
+        }
+
+        impl<B1: BitAccess, B: BlockType> $assign_trait<&BitVec<B>> for BitSlice<B1> {
+            fn $assign_fn(&mut self, rhs: &BitVec<B>) {
+                assign_op(self, &rhs.data, |$l, $r| $op)
+            }
+        }
+
+        impl<B: BlockType, B2: BitGet> $assign_trait<&BitSlice<B2>> for BitVec<B> {
+            fn $assign_fn(&mut self, rhs: &BitSlice<B2>) {
+                assign_op(self.as_mut(), rhs, |$l, $r| $op)
+            }
+        }
+
+        impl<B: BlockType> $assign_trait<&BitVec<B>> for BitVec<B> {
+            fn $assign_fn(&mut self, rhs: &BitVec<B>) {
+                assign_op_aligned(self, rhs, |$l, $r| $op)
+            }
+        }
+
+        impl<B1: BitGet, B2: BitGet> $op_trait<&BitSlice<B2>> for &BitSlice<B1> {
+            type Output = BitVec;
+
+            fn $op_fn(self, rhs: &BitSlice<B2>) -> BitVec {
+                let mut result = BitVec::from(self);
+                result.$assign_fn(rhs);
+                result
+            }
+        }
+
+        impl<B1: BitGet, B: BlockType> $op_trait<&BitVec<B>> for &BitSlice<B1> {
+            type Output = BitVec;
+
+            fn $op_fn(self, rhs: &BitVec<B>) -> BitVec {
+                let mut result = BitVec::from(self);
+                result.$assign_fn(&rhs.data);
+                result
+            }
+        }
+
+        impl<B: BlockType, B2: BitGet> $op_trait<&BitSlice<B2>> for &BitVec<B> {
+            type Output = BitVec<B>;
+
+            fn $op_fn(self, rhs: &BitSlice<B2>) -> BitVec<B> {
+                self.clone().$op_fn(rhs)
+            }
+        }
+
+        impl<B: BlockType> $op_trait<&BitVec<B>> for &BitVec<B> {
+            type Output = BitVec<B>;
+
+            fn $op_fn(self, rhs: &BitVec<B>) -> BitVec<B> {
+                self.clone().$op_fn(rhs)
+            }
+        }
+
+        impl<B: BlockType, B2: BitGet> $op_trait<&BitSlice<B2>> for BitVec<B> {
+            type Output = BitVec<B>;
+
+            fn $op_fn(mut self, rhs: &BitSlice<B2>) -> BitVec<B> {
+                self.$assign_fn(rhs);
+                self
+            }
+        }
+
+        impl<B: BlockType> $op_trait<&BitVec<B>> for BitVec<B> {
+            type Output = BitVec<B>;
+
+            fn $op_fn(mut self, rhs: &BitVec<B>) -> BitVec<B> {
+                self.$assign_fn(rhs);
+                self
+            }
+        }
+    };
+}
+
+bit_op! { BitAnd, bitand, BitAndAssign, bitand_assign, |l, r| l & r }
+bit_op! { BitOr, bitor, BitOrAssign, bitor_assign, |l, r| l | r }
+bit_op! { BitXor, bitxor, BitXorAssign, bitxor_assign, |l, r| l ^ r }
+
+impl<B: BlockType> Not for BitVec<B> {
+    type Output = BitVec<B>;
+
+    fn not(mut self) -> BitVec<B> {
+        for block in self.data.backing_mut().iter_mut() {
+            *block = !*block;
+        }
+        self.clear_unused_bits();
+        self
+    }
+}
+
+impl<B: BlockType> Not for &BitVec<B> {
+    type Output = BitVec<B>;
+
+    fn not(self) -> BitVec<B> {
+        !self.clone()
+    }
+}
+
+impl<Backing: BitGet> Not for &BitSlice<Backing> {
+    type Output = BitVec;
+
+    fn not(self) -> BitVec {
+        !BitVec::from(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bit_vec::{BitModify, BitVec};
+    use crate::test_util::pattern;
+
+    #[test]
+    fn bit_vec_ops_test() {
+        let a: BitVec = pattern(300, 3);
+        let b: BitVec = pattern(300, 5);
+
+        let and = &a & &b;
+        let or = &a | &b;
+        let xor = &a ^ &b;
+        let not = !&a;
+        for i in 0..a.len() {
+            assert_eq!(a.get(i) && b.get(i), and.get(i), "incorrect and at index {i}");
+            assert_eq!(a.get(i) || b.get(i), or.get(i), "incorrect or at index {i}");
+            assert_eq!(a.get(i) ^ b.get(i), xor.get(i), "incorrect xor at index {i}");
+            assert_eq!(!a.get(i), not.get(i), "incorrect not at index {i}");
+        }
+        assert_eq!(a.len() - a.count_ones(), not.count_ones(), "bits past the end are set");
+        assert_eq!(and, a.clone() & &b);
+        assert_eq!(or, a.clone() | &b);
+        assert_eq!(xor, a.clone() ^ &b);
+        assert_eq!(not, !a.clone());
+
+        let mut c = a.clone();
+        c &= &b;
+        assert_eq!(and, c);
+        let mut c = a.clone();
+        c |= &b;
+        assert_eq!(or, c);
+        let mut c = a.clone();
+        c ^= &b;
+        assert_eq!(xor, c);
+    }
+
+    #[test]
+    fn unaligned_slice_ops_test() {
+        let a: BitVec = pattern(400, 3);
+        let b: BitVec = pattern(400, 7);
+        let sa = a.slice(13..313);
+        let sb = b.slice(70..370);
+
+        let and = &sa & &sb;
+        let or = &sa | &sb;
+        let xor = &sa ^ &sb;
+        let not = !&sa;
+        assert_eq!(300, and.len(), "incorrect length");
+        for i in 0..sa.len() {
+            assert_eq!(sa.get(i) && sb.get(i), and.get(i), "incorrect and at index {i}");
+            assert_eq!(sa.get(i) || sb.get(i), or.get(i), "incorrect or at index {i}");
+            assert_eq!(sa.get(i) ^ sb.get(i), xor.get(i), "incorrect xor at index {i}");
+            assert_eq!(!sa.get(i), not.get(i), "incorrect not at index {i}");
+        }
+
+        assert_eq!(and.count_ones(), sa.and_count(&sb), "incorrect and count");
+        assert_eq!(or.count_ones(), sa.or_count(&sb), "incorrect or count");
+        assert_eq!(xor.count_ones(), sa.xor_count(&sb), "incorrect xor count");
+
+        let c = BitVec::from(&sb);
+        assert_eq!(and, &sa & &c);
+        assert_eq!(and, &c & &sa);
+        assert_eq!(and, c.clone() & &sa);
+    }
+
+    #[test]
+    fn slice_assign_test() {
+        let mut a: BitVec = pattern(400, 3);
+        let expected: BitVec = pattern(400, 3);
+        let b: BitVec = pattern(400, 7);
+
+        {
+            let mut slice = a.slice_mut(13..313);
+            slice ^= &b.slice(70..370);
+            slice |= &b.slice(0..300);
+        }
+
+        for i in 0..a.len() {
+            let value = if (13..313).contains(&i) {
+                (expected.get(i) ^ b.get(i + 57)) | b.get(i - 13)
+            } else {
+                expected.get(i)
+            };
+            assert_eq!(value, a.get(i), "incorrect value at index {i}");
+        }
+
+        let mut a: BitVec = pattern(130, 3);
+        let b: BitVec = pattern(130, 7);
+        {
+            let mut slice = a.slice_mut(..);
+            slice &= &b;
+        }
+        assert_eq!(&pattern::<BitVec>(130, 3) & &b, a);
+    }
+
+    #[test]
+    fn count_test() {
+        let mut bv = BitVec::new(200);
+        for i in (0..bv.len()).step_by(3) {
+            bv.set_bit(i, true);
+        }
+        assert_eq!(67, bv.count_ones());
+        assert_eq!(133, bv.count_zeros());
+        assert_eq!(33, bv.slice(1..101).count_ones());
+    }
+
+    #[test]
+    #[should_panic]
+    fn length_mismatch_test() {
+        let a = BitVec::new(100);
+        let b = BitVec::new(101);
+        let _ = &a & &b;
+    }
+
+    #[test]
+    #[should_panic]
+    fn count_length_mismatch_test() {
+        let a = BitVec::new(100);
+        a.and_count(&a.slice(1..));
+    }
+}
@@ -0,0 +1,403 @@
+use alloc::vec::Vec;
+use core::ops::RangeBounds;
+
+use crate::bit_vec::{Word, WORD_SIZE};
+
+use super::{BitGet, BitModify, BitSlice};
+
+impl<Backing: BitModify> BitSlice<Backing> {
+    /// Copies all bits from another slice into this slice. The bits are transferred a word at a
+    /// time, regardless of the offsets of the slices in their backing data.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The slice to copy the bits from. It must have the same length as this slice.
+    ///
+    /// # Panics
+    ///
+    /// If the lengths of the slices differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitModify, BitVec};
+    ///
+    /// let mut src = BitVec::new(100);
+    /// src.set_bit(3, true);
+    /// src.set_bit(90, true);
+    ///
+    /// let mut bv = BitVec::new(200);
+    /// bv.slice_mut(7..107).copy_from_bitslice(&src.slice(..));
+    ///
+    /// assert_eq!(vec![10, 97], bv.iter_ones().collect::<Vec<_>>());
+    /// ```
+    pub fn copy_from_bitslice<Other: BitGet>(&mut self, src: &BitSlice<Other>) {
+        if src.len() != self.len() {
+            panic!(
+                "source length is {} but destination length is {}",
+                src.len(),
+                self.len()
+            )
+        }
+        for i in (0..self.len()).step_by(WORD_SIZE) {
+            let len = WORD_SIZE.min(self.len() - i);
+            // SAFETY: Both slices have the same length and the range is in bounds
+            unsafe { self.set_bits_unchecked(i, len, src.get_bits_unchecked(i, len)) }
+        }
+    }
+
+    /// Sets all bits in the range from `start` to `end` to zero, a word at a time.
+    ///
+    /// # Safety
+    ///
+    /// The range must be in bounds of this slice.
+    unsafe fn clear_range_unchecked(&mut self, start: usize, end: usize) {
+        for i in (start..end).step_by(WORD_SIZE) {
+            self.set_bits_unchecked(i, WORD_SIZE.min(end - i), 0);
+        }
+    }
+}
+
+impl<Backing: BitGet + BitModify> BitSlice<Backing> {
+    /// Copies the bits in a range of this slice to another position in the same slice. The
+    /// ranges may overlap, and the bits are transferred a word at a time even if the source and
+    /// destination are not aligned to each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The range of bits to copy.
+    /// * `dest` - The index the first bit of the range is copied to.
+    ///
+    /// # Panics
+    ///
+    /// If the source range is invalid or the destination range exceeds the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let mut bv: BitVec = [true, true, false, true, false, false].into_iter().collect();
+    /// bv.copy_within(0..4, 2);
+    ///
+    /// let expected: BitVec = [true, true, true, true, false, true].into_iter().collect();
+    /// assert_eq!(expected, bv);
+    /// ```
+    pub fn copy_within(&mut self, src: impl RangeBounds<usize>, dest: usize) {
+        let range = self.slice(src);
+        let (start, len) = (range.start - self.start, range.len());
+        if dest + len > self.len() {
+            panic!(
+                "destination end is {} but length is {}",
+                dest + len,
+                self.len()
+            )
+        }
+        // SAFETY: Both ranges were checked to be in bounds
+        unsafe { self.copy_within_unchecked(start, dest, len) }
+    }
+
+    /// Copies `len` bits starting at `src` to `dest` a word at a time. If the destination lies
+    /// after the source, the bits are copied starting at the back so that overlapping bits are
+    /// read before they are overwritten.
+    ///
+    /// # Safety
+    ///
+    /// Both ranges must be in bounds of this slice.
+    unsafe fn copy_within_unchecked(&mut self, src: usize, dest: usize, len: usize) {
+        if dest <= src {
+            for i in (0..len).step_by(WORD_SIZE) {
+                let chunk = WORD_SIZE.min(len - i);
+                self.set_bits_unchecked(dest + i, chunk, self.get_bits_unchecked(src + i, chunk));
+            }
+        } else {
+            let mut end = len;
+            while end > 0 {
+                let chunk = WORD_SIZE.min(end);
+                end -= chunk;
+                self.set_bits_unchecked(
+                    dest + end,
+                    chunk,
+                    self.get_bits_unchecked(src + end, chunk),
+                );
+            }
+        }
+    }
+
+    /// Shifts all bits of this slice towards the end by `n` positions, i.e. the bit at index `i`
+    /// moves to index `i + n`. The last `n` bits are dropped and the first `n` bits are set to
+    /// zero. This matches a left shift of the words returned by
+    /// [`WordAccess::get_word`](crate::bit_vec::WordAccess::get_word).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to shift by. If it is at least the length of this
+    ///   slice, all bits are set to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitModify, BitVec, WordAccess};
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.set_bits(0, 8, 0b1011_0001);
+    /// bv.shift_left(60);
+    ///
+    /// assert_eq!(0b0001 << 60, bv.get_word(0));
+    /// assert_eq!(0b1011, bv.get_word(1));
+    /// ```
+    pub fn shift_left(&mut self, n: usize) {
+        let n = n.min(self.len());
+        // SAFETY: All ranges lie within this slice
+        unsafe {
+            self.copy_within_unchecked(0, n, self.len() - n);
+            self.clear_range_unchecked(0, n);
+        }
+    }
+
+    /// Shifts all bits of this slice towards the start by `n` positions, i.e. the bit at index
+    /// `i` moves to index `i - n`. The first `n` bits are dropped and the last `n` bits are set to
+    /// zero. This matches a right shift of the words returned by
+    /// [`WordAccess::get_word`](crate::bit_vec::WordAccess::get_word).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to shift by. If it is at least the length of this
+    ///   slice, all bits are set to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitModify, BitVec, WordAccess};
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.set_bits(60, 8, 0b1011_0001);
+    /// bv.shift_right(58);
+    ///
+    /// assert_eq!(0b10_1100_0100, bv.get_word(0));
+    /// assert_eq!(0, bv.get_word(1));
+    /// ```
+    pub fn shift_right(&mut self, n: usize) {
+        let n = n.min(self.len());
+        // SAFETY: All ranges lie within this slice
+        unsafe {
+            self.copy_within_unchecked(n, 0, self.len() - n);
+            self.clear_range_unchecked(self.len() - n, self.len());
+        }
+    }
+
+    /// Rotates all bits of this slice towards the start by `n` positions like
+    /// [`slice::rotate_left`], i.e. the bit at index `n % len` moves to index 0. Note that this is
+    /// the opposite direction of [`BitSlice::shift_left`], which matches a shift of the words.
+    /// The bits are moved a word at a time, using a temporary buffer for the smaller one of both
+    /// parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to rotate by. It may exceed the length of this slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let mut bv: BitVec = [true, true, false, false, true].into_iter().collect();
+    /// bv.rotate_left(2);
+    ///
+    /// let expected: BitVec = [false, false, true, true, true].into_iter().collect();
+    /// assert_eq!(expected, bv);
+    /// ```
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len();
+        if n > self.len() / 2 {
+            return self.rotate_right(self.len() - n);
+        }
+        let rest = self.len() - n;
+        // SAFETY: All ranges lie within this slice
+        unsafe {
+            let saved = self.save_range_unchecked(0, n);
+            self.copy_within_unchecked(n, 0, rest);
+            self.slice_unchecked_mut(rest..)
+                .copy_from_bitslice(&BitSlice::new(saved, 0, n));
+        }
+    }
+
+    /// Rotates all bits of this slice towards the end by `n` positions like
+    /// [`slice::rotate_right`], i.e. the bit at index `i` moves to index `(i + n) % len`. Note
+    /// that this is the opposite direction of [`BitSlice::shift_right`], which matches a shift of
+    /// the words. The bits are moved a word at a time, using a temporary buffer for the smaller
+    /// one of both parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to rotate by. It may exceed the length of this slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let mut bv: BitVec = [true, true, false, false, true].into_iter().collect();
+    /// bv.rotate_right(2);
+    ///
+    /// let expected: BitVec = [false, true, true, true, false].into_iter().collect();
+    /// assert_eq!(expected, bv);
+    /// ```
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len();
+        if n > self.len() / 2 {
+            return self.rotate_left(self.len() - n);
+        }
+        let rest = self.len() - n;
+        // SAFETY: All ranges lie within this slice
+        unsafe {
+            let saved = self.save_range_unchecked(rest, self.len());
+            self.copy_within_unchecked(0, n, rest);
+            self.slice_unchecked_mut(..n)
+                .copy_from_bitslice(&BitSlice::new(saved, 0, n));
+        }
+    }
+
+    /// Copies the bits in the range from `start` to `end` into a vector of words.
+    ///
+    /// # Safety
+    ///
+    /// The range must be in bounds of this slice.
+    unsafe fn save_range_unchecked(&self, start: usize, end: usize) -> Vec<Word> {
+        (start..end)
+            .step_by(WORD_SIZE)
+            .map(|i| self.get_bits_unchecked(i, WORD_SIZE.min(end - i)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use crate::bit_vec::{BitGet, BitVec};
+    use crate::test_util::pattern;
+
+    fn from_bools<B: crate::traits::BlockType>(bits: &[bool]) -> BitVec<B> {
+        bits.iter().copied().collect()
+    }
+
+    #[test]
+    fn copy_from_bitslice_test() {
+        let src: Vec<bool> = pattern(300, 2);
+        let src_bv: BitVec = from_bools(&src);
+        for (src_start, dest_start, len) in [(0, 0, 300), (5, 70, 200), (63, 1, 129), (17, 64, 0)] {
+            let mut bv = BitVec::<u8>::zeroed(400);
+            bv.slice_mut(dest_start..dest_start + len)
+                .copy_from_bitslice(&src_bv.slice(src_start..src_start + len));
+            for i in 0..400 {
+                let expected =
+                    (dest_start..dest_start + len).contains(&i) && src[i - dest_start + src_start];
+                assert_eq!(
+                    expected,
+                    bv.get_bit(i),
+                    "copy {src_start} to {dest_start}, index {i}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_from_bitslice_length_mismatch_test() {
+        let mut bv = BitVec::new(100);
+        let src = BitVec::new(99);
+        bv.copy_from_bitslice(&src.slice(..));
+    }
+
+    #[test]
+    fn copy_within_test() {
+        let bits: Vec<bool> = pattern(500, 2);
+        for (start, end, dest) in [
+            (0, 500, 0),
+            (0, 300, 3),
+            (3, 300, 0),
+            (10, 400, 100),
+            (100, 400, 10),
+            (64, 128, 0),
+            (7, 7, 500),
+        ] {
+            let mut bv: BitVec = from_bools(&bits);
+            bv.copy_within(start..end, dest);
+
+            let mut expected = bits.clone();
+            expected.copy_within(start..end, dest);
+            assert!(bv.iter().eq(expected), "copy {start}..{end} to {dest}");
+        }
+    }
+
+    #[test]
+    fn copy_within_slice_test() {
+        let bits: Vec<bool> = pattern(300, 2);
+        let mut bv: BitVec<u16> = from_bools(&bits);
+        bv.slice_mut(20..270).copy_within(5..150, 77);
+
+        let mut expected = bits.clone();
+        expected[20..270].copy_within(5..150, 77);
+        assert!(bv.iter().eq(expected));
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_within_out_of_bounds_test() {
+        let mut bv = BitVec::new(100);
+        bv.copy_within(10..60, 51);
+    }
+
+    #[test]
+    fn shift_test() {
+        let bits: Vec<bool> = pattern(333, 2);
+        for n in [0, 1, 13, 64, 100, 200, 332, 333, 1000] {
+            let mut bv: BitVec = from_bools(&bits);
+            bv.slice_mut(1..).shift_left(n);
+            let n = n.min(332);
+            let expected = bits[..1]
+                .iter()
+                .chain(&[false; 333][..n])
+                .chain(&bits[1..333 - n]);
+            assert!(bv.iter().eq(expected.copied()), "shift left by {n}");
+
+            let mut bv: BitVec = from_bools(&bits);
+            bv.slice_mut(1..).shift_right(n);
+            let expected = bits[..1]
+                .iter()
+                .chain(&bits[1 + n..])
+                .chain(&[false; 333][..n]);
+            assert!(bv.iter().eq(expected.copied()), "shift right by {n}");
+        }
+    }
+
+    #[test]
+    fn rotate_test() {
+        let bits: Vec<bool> = pattern(333, 2);
+        for n in [0, 1, 13, 64, 100, 166, 167, 200, 332, 333, 1000] {
+            let mut bv: BitVec<u32> = from_bools(&bits);
+            bv.rotate_left(n);
+            let mut expected = bits.clone();
+            expected.rotate_left(n % 333);
+            assert!(bv.iter().eq(expected), "rotate left by {n}");
+
+            let mut bv: BitVec<u32> = from_bools(&bits);
+            bv.rotate_right(n);
+            let mut expected = bits.clone();
+            expected.rotate_right(n % 333);
+            assert!(bv.iter().eq(expected), "rotate right by {n}");
+        }
+
+        let mut empty = BitVec::new(0);
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+}
@@ -1,8 +1,13 @@
-use super::{BitGet, BitModify};
+use super::{BitGet, BitModify, Word, WordAccess, WORD_MASK, WORD_SIZE};
+use crate::serialize::{self, SerializeError};
 
+mod copying;
+mod positions;
 mod slicing;
 mod trait_impls;
 
+pub use positions::{BitPositions, ExactBitPositions, IterOnes, IterZeros};
+
 /// A view into a segment of a type which supports `BitGet` and/or `BitModify` if the backing type supports it respectively.
 ///
 /// Properties:
@@ -84,6 +89,77 @@ impl<Backing> BitSlice<Backing> {
     pub fn backing(&self) -> &Backing {
         &self.backing
     }
+
+    /// Grants mutable access to the backing type of this bit slice
+    #[inline]
+    pub(crate) fn backing_mut(&mut self) -> &mut Backing {
+        &mut self.backing
+    }
+
+    /// Consumes this bit slice and returns its backing type.
+    #[inline]
+    pub fn into_backing(self) -> Backing {
+        self.backing
+    }
+
+    /// Changes the length of this slice by moving its end index.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The new length of this slice.
+    #[inline]
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.end = self.start + len;
+    }
+}
+
+impl<'a> BitSlice<&'a [Word]> {
+    /// Creates a read-only view of a bit vector written by
+    /// [`BitVec::write_to`](super::BitVec::write_to) directly from a buffer, without copying it.
+    ///
+    /// This is intended for memory mapped files. The buffer must contain exactly one bit vector
+    /// and must be aligned to 8 bytes, which is always the case for memory mapped files.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The buffer containing the bit vector.
+    ///
+    /// returns: The view or an error if the buffer is misaligned or malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use succinct_neo::bit_vec::{BitGet, BitVec, slice::BitSlice};
+    ///
+    /// let bv: BitVec = (0..100).map(|i| i % 3 == 0).collect();
+    /// let mut buf = Vec::new();
+    /// bv.write_to(&mut buf).unwrap();
+    ///
+    /// // Copy the bytes into a buffer aligned to 8 bytes, which a memory map already is
+    /// let mut words = vec![0u64; buf.len() / 8];
+    /// let bytes = unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), buf.len()) };
+    /// bytes.copy_from_slice(&buf);
+    ///
+    /// let slice = BitSlice::from_bytes(bytes).unwrap();
+    /// assert_eq!(100, slice.len());
+    /// assert!(slice.get_bit(99));
+    /// # }
+    /// ```
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, SerializeError> {
+        let mut reader = bytes;
+        serialize::read_header(&mut reader, super::BIT_VEC_MAGIC)?;
+        let size = serialize::read_usize(&mut reader)?;
+        let words = serialize::cast_slice::<Word>(reader)?;
+        if words.len() != size.div_ceil(WORD_SIZE) {
+            return Err(SerializeError::Invalid("length does not match the number of words"));
+        }
+        let rest_bits = size & WORD_MASK;
+        if rest_bits != 0 && words[words.len() - 1] >> rest_bits != 0 {
+            return Err(SerializeError::Invalid("bits past the end of the bit vector are set"));
+        }
+        Ok(Self::new(words, 0, size))
+    }
 }
 
 impl<Backing: BitGet> BitSlice<Backing> {
@@ -110,6 +186,49 @@ impl<Backing: BitGet> BitSlice<Backing> {
         }
     }
 
+    /// Gets an iterator over the positions of all ones in this slice in ascending order.
+    ///
+    /// The iterator only knows the number of ones it yields if it is told using
+    /// [`BitPositions::with_count`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitModify, BitVec};
+    ///
+    /// let mut bv = BitVec::new(200);
+    /// bv.set_bit(6, true);
+    /// bv.set_bit(70, true);
+    /// bv.set_bit(150, true);
+    ///
+    /// assert_eq!(vec![6, 70, 150], bv.iter_ones().collect::<Vec<_>>());
+    /// assert_eq!(vec![145, 65, 1], bv.slice(5..).iter_ones().rev().collect::<Vec<_>>());
+    ///
+    /// let ones = bv.iter_ones().with_count(bv.count_ones());
+    /// assert_eq!(3, ones.len());
+    /// ```
+    pub fn iter_ones(&self) -> IterOnes<&Self> {
+        BitPositions::new(self)
+    }
+
+    /// Gets an iterator over the positions of all zeroes in this slice in ascending order.
+    ///
+    /// The iterator only knows the number of zeroes it yields if it is told using
+    /// [`BitPositions::with_count`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::BitVec;
+    ///
+    /// let bv: BitVec = [true, false, true, true, false].into_iter().collect();
+    ///
+    /// assert_eq!(vec![1, 4], bv.iter_zeros().collect::<Vec<_>>());
+    /// ```
+    pub fn iter_zeros(&self) -> IterZeros<&Self> {
+        BitPositions::new(self)
+    }
+
     /// Splits the bit slice into two disjunct parts at a given index, returning read-only views into each
     /// part.
     ///
@@ -286,6 +405,8 @@ impl<Backing> Iter<Backing> {
 #[cfg(test)]
 mod test {
     use crate::bit_vec::BitVec;
+    #[cfg(feature = "std")]
+    use crate::{bit_vec::BitGet, serialize::{AlignedBuf, SerializeError}};
     use super::{BitModify, BitSlice};
     
     #[test]
@@ -396,4 +517,50 @@ mod test {
             "mutable right-split part of mutable slice not the same"
         );
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_bytes_test() {
+        for len in [0, 1, 64, 100, 1000] {
+            let bv: BitVec = (0..len).map(|i| i % 3 == 0).collect();
+            let mut buf = Vec::new();
+            bv.write_to(&mut buf).unwrap();
+            let buf = AlignedBuf::new(&buf);
+
+            let slice = BitSlice::from_bytes(buf.bytes()).unwrap();
+            assert_eq!(bv.len(), slice.len(), "length incorrect");
+            assert_eq!(bv.raw(), *slice.backing(), "words incorrect");
+            assert!((0..len).all(|i| bv.get_bit(i) == slice.get_bit(i)));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_bytes_invalid_test() {
+        let bv: BitVec = (0..100).map(|i| i % 3 == 0).collect();
+        let mut buf = Vec::new();
+        bv.write_to(&mut buf).unwrap();
+        let mut padded = buf.clone();
+        padded.extend_from_slice(&[0; 8]);
+        let padded = AlignedBuf::new(&padded);
+        let buf = AlignedBuf::new(&buf);
+        let bytes = buf.bytes();
+
+        assert!(matches!(
+            BitSlice::from_bytes(&padded.bytes()[1..]),
+            Err(SerializeError::InvalidMagic { .. })
+        ));
+        // Misaligned words
+        let mut shifted = vec![0];
+        shifted.extend_from_slice(bytes);
+        let shifted = AlignedBuf::new(&shifted);
+        assert!(matches!(
+            BitSlice::from_bytes(&shifted.bytes()[1..]),
+            Err(SerializeError::Misaligned(_))
+        ));
+        // Missing and trailing words
+        assert!(BitSlice::from_bytes(&bytes[..bytes.len() - 8]).is_err());
+        assert!(BitSlice::from_bytes(&padded.bytes()[..bytes.len() + 8]).is_err());
+        assert!(BitSlice::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
 }
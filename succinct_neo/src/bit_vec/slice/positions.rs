@@ -0,0 +1,273 @@
+use core::iter::FusedIterator;
+
+use crate::bit_vec::{Word, WordAccess, WORD_SIZE};
+
+/// An iterator over the positions of all ones (if `TARGET` is `true`) or zeroes (if `TARGET` is
+/// `false`) in a datastructure supporting [`WordAccess`].
+///
+/// The iterator reads the backing data a word at a time and skips over all non-matching bits
+/// using trailing zero counts, which makes it fast on sparse data.
+/// It can be created using [`BitSlice::iter_ones`](super::BitSlice::iter_ones) and
+/// [`BitSlice::iter_zeros`](super::BitSlice::iter_zeros).
+#[derive(Debug, Clone)]
+pub struct BitPositions<Backing, const TARGET: bool> {
+    backing: Backing,
+    /// The index of the first word which has not been loaded yet
+    current: usize,
+    /// The index after the last word which has not been loaded yet
+    end: usize,
+    /// The remaining matching bits of the word loaded at the front
+    front_word: Word,
+    /// The index of the least significant bit in the front word
+    front_base: usize,
+    /// The remaining matching bits of the word loaded at the back
+    back_word: Word,
+    /// The index of the least significant bit in the back word
+    back_base: usize,
+}
+
+/// An iterator over the positions of all ones in a bit slice.
+pub type IterOnes<Backing> = BitPositions<Backing, true>;
+
+/// An iterator over the positions of all zeroes in a bit slice.
+pub type IterZeros<Backing> = BitPositions<Backing, false>;
+
+impl<Backing: WordAccess, const TARGET: bool> BitPositions<Backing, TARGET> {
+    /// Creates a new iterator over the positions of all ones or zeroes in the backing data
+    /// structure.
+    pub fn new(backing: Backing) -> Self {
+        let end = backing.word_len();
+        Self {
+            backing,
+            current: 0,
+            end,
+            front_word: 0,
+            front_base: 0,
+            back_word: 0,
+            back_base: 0,
+        }
+    }
+
+    /// Turns this into an iterator that knows how many positions it will yield, so it can
+    /// implement [`ExactSizeIterator`].
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of positions this iterator has yet to yield, e.g. the number of
+    ///   ones in the slice if this iterator has not been advanced yet.
+    pub fn with_count(self, count: usize) -> ExactBitPositions<Backing, TARGET> {
+        ExactBitPositions {
+            inner: self,
+            remaining: count,
+        }
+    }
+
+    /// Loads the word with the given index and keeps only the bits we search for.
+    #[inline]
+    fn load(&self, index: usize) -> Word {
+        // SAFETY: The iterator only loads words inside the backing data structure
+        let word = unsafe { self.backing.get_word_unchecked(index) };
+        if TARGET {
+            return word;
+        }
+        // Bits past the end are zero, so we need to get rid of them after inverting
+        let len = self.backing.bit_len() - index * WORD_SIZE;
+        if len >= WORD_SIZE {
+            !word
+        } else {
+            !word & ((1 << len) - 1)
+        }
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> Iterator for BitPositions<Backing, TARGET> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front_word == 0 {
+            if self.current >= self.end {
+                // Everything else has been loaded into the back word
+                if self.back_word == 0 {
+                    return None;
+                }
+                core::mem::swap(&mut self.front_word, &mut self.back_word);
+                self.front_base = self.back_base;
+                break;
+            }
+            self.front_word = self.load(self.current);
+            self.front_base = self.current * WORD_SIZE;
+            self.current += 1;
+        }
+
+        let index = self.front_word.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.front_word &= self.front_word - 1;
+        Some(self.front_base + index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let loaded = (self.front_word.count_ones() + self.back_word.count_ones()) as usize;
+        (loaded, Some(loaded + (self.end - self.current) * WORD_SIZE))
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> DoubleEndedIterator
+    for BitPositions<Backing, TARGET>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.back_word == 0 {
+            if self.current >= self.end {
+                // Everything else has been loaded into the front word
+                if self.front_word == 0 {
+                    return None;
+                }
+                core::mem::swap(&mut self.front_word, &mut self.back_word);
+                self.back_base = self.front_base;
+                break;
+            }
+            self.end -= 1;
+            self.back_word = self.load(self.end);
+            self.back_base = self.end * WORD_SIZE;
+        }
+
+        let index = WORD_SIZE - 1 - self.back_word.leading_zeros() as usize;
+        // Clear the highest set bit
+        self.back_word &= !(1 << index);
+        Some(self.back_base + index)
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> FusedIterator for BitPositions<Backing, TARGET> {}
+
+/// An iterator over the positions of all ones or zeroes in a bit slice which knows the number
+/// of positions it yields.
+///
+/// This is created using [`BitPositions::with_count`].
+#[derive(Debug, Clone)]
+pub struct ExactBitPositions<Backing, const TARGET: bool> {
+    inner: BitPositions<Backing, TARGET>,
+    remaining: usize,
+}
+
+impl<Backing: WordAccess, const TARGET: bool> Iterator for ExactBitPositions<Backing, TARGET> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.inner.next();
+        if next.is_some() {
+            self.remaining -= 1;
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> DoubleEndedIterator
+    for ExactBitPositions<Backing, TARGET>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.inner.next_back();
+        if next.is_some() {
+            self.remaining -= 1;
+        }
+        next
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> ExactSizeIterator
+    for ExactBitPositions<Backing, TARGET>
+{
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<Backing: WordAccess, const TARGET: bool> FusedIterator
+    for ExactBitPositions<Backing, TARGET>
+{
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bit_vec::BitVec;
+    use crate::test_util::pattern;
+
+    #[test]
+    fn iter_ones_test() {
+        let bv: BitVec = pattern(500, 4);
+        let expected = (0..bv.len()).filter(|&i| bv.get(i)).collect::<Vec<_>>();
+        assert_eq!(expected, bv.iter_ones().collect::<Vec<_>>());
+
+        let slice = bv.slice(37..421);
+        let expected = (0..slice.len()).filter(|&i| slice.get(i)).collect::<Vec<_>>();
+        assert_eq!(expected, slice.iter_ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iter_zeros_test() {
+        let bv: BitVec = pattern(500, 4);
+        let expected = (0..bv.len()).filter(|&i| !bv.get(i)).collect::<Vec<_>>();
+        assert_eq!(expected, bv.iter_zeros().collect::<Vec<_>>());
+
+        let slice = bv.slice(37..421);
+        let expected = (0..slice.len()).filter(|&i| !slice.get(i)).collect::<Vec<_>>();
+        assert_eq!(expected, slice.iter_zeros().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn double_ended_test() {
+        let bv: BitVec = pattern(500, 4);
+        let slice = bv.slice(37..421);
+        let expected = (0..slice.len()).filter(|&i| slice.get(i)).collect::<Vec<_>>();
+
+        let reversed = slice.iter_ones().rev().collect::<Vec<_>>();
+        assert_eq!(expected.iter().rev().copied().collect::<Vec<_>>(), reversed);
+
+        // Alternate between both ends, so that they meet in the middle
+        let mut iter = slice.iter_ones();
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        while let Some(v) = iter.next() {
+            front.push(v);
+            match iter.next_back() {
+                Some(v) => back.push(v),
+                None => break,
+            }
+        }
+        front.extend(back.into_iter().rev());
+        assert_eq!(expected, front);
+        assert_eq!(None, iter.next());
+        assert_eq!(None, iter.next_back());
+    }
+
+    #[test]
+    fn exact_size_test() {
+        let bv: BitVec = pattern(500, 4);
+        let count = bv.count_ones();
+        let mut iter = bv.iter_ones().with_count(count);
+        assert_eq!(count, iter.len());
+        iter.next();
+        iter.next_back();
+        assert_eq!(count - 2, iter.len());
+        assert_eq!(count - 2, iter.count());
+
+        let mut iter = bv.iter_zeros().with_count(bv.count_zeros());
+        assert_eq!(bv.count_zeros(), iter.len());
+        iter.next();
+        assert_eq!(bv.count_zeros() - 1, iter.len());
+    }
+
+    #[test]
+    fn empty_test() {
+        let bv = BitVec::new(0);
+        assert_eq!(None, bv.iter_ones().next());
+        assert_eq!(None, bv.iter_zeros().next_back());
+
+        let bv = BitVec::new(130);
+        assert_eq!(None, bv.iter_ones().next());
+        assert_eq!(Some(129), bv.iter_zeros().next_back());
+        assert_eq!(130, bv.iter_zeros().count());
+    }
+}
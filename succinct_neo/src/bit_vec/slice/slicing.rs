@@ -1,4 +1,4 @@
-use std::ops::{Bound, RangeBounds};
+use core::ops::{Bound, RangeBounds};
 
 use super::BitSlice;
 
@@ -189,18 +189,18 @@ impl<Backing> BitSlice<Backing> {
 
 #[cfg(test)]
 mod test {
-    use std::ops::{Bound, RangeBounds};
+    use core::ops::{Bound, RangeBounds};
 
     use crate::bit_vec::BitVec;
     /// Range with exclusive start and end index
     struct ExclusiveRange<const S: usize, const E: usize>;
 
     impl<const S: usize, const E: usize> RangeBounds<usize> for ExclusiveRange<S, E> {
-        fn start_bound(&self) -> std::ops::Bound<&usize> {
+        fn start_bound(&self) -> core::ops::Bound<&usize> {
             Bound::Excluded(&S)
         }
 
-        fn end_bound(&self) -> std::ops::Bound<&usize> {
+        fn end_bound(&self) -> core::ops::Bound<&usize> {
             Bound::Excluded(&E)
         }
     }
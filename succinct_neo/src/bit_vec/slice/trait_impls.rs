@@ -1,4 +1,7 @@
-use super::{BitGet, BitModify, BitSlice, Iter};
+use crate::bit_vec::traits::check_bits_value;
+use crate::bit_vec::{Word, WORD_SIZE};
+
+use super::{BitGet, BitModify, BitSlice, Iter, WordAccess};
 
 impl<B1: BitGet, B2: BitGet> PartialEq<BitSlice<B2>> for BitSlice<B1> {
     fn eq(&self, other: &BitSlice<B2>) -> bool {
@@ -6,7 +9,25 @@ impl<B1: BitGet, B2: BitGet> PartialEq<BitSlice<B2>> for BitSlice<B1> {
             return false;
         }
 
-        Iterator::eq(self.into_iter(), other.into_iter())
+        // Compare a word at a time
+        (0..self.len()).step_by(WORD_SIZE).all(|i| {
+            let len = WORD_SIZE.min(self.len() - i);
+            // SAFETY: Both slices have the same length and the range is in bounds
+            unsafe { self.get_bits_unchecked(i, len) == other.get_bits_unchecked(i, len) }
+        })
+    }
+}
+
+impl<Backing> BitSlice<Backing> {
+    /// Panics if `len` exceeds the word size or the bit range is out of bounds of this slice.
+    #[inline]
+    fn check_bits_range(&self, index: usize, len: usize) {
+        if len > WORD_SIZE {
+            panic!("length is {len} but at most {WORD_SIZE} bits can be accessed at once")
+        }
+        if index + len > self.len() {
+            panic!("range end is {} but length is {}", index + len, self.len())
+        }
     }
 }
 
@@ -21,6 +42,30 @@ impl<Backing: BitGet> BitGet for BitSlice<Backing> {
         }
         unsafe { self.get_bit_unchecked(index) }
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        self.backing.get_bits_unchecked(self.start + index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        self.check_bits_range(index, len);
+        unsafe { self.get_bits_unchecked(index, len) }
+    }
+}
+
+impl<Backing: BitGet> WordAccess for BitSlice<Backing> {
+    #[inline]
+    fn bit_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    unsafe fn get_word_unchecked(&self, index: usize) -> Word {
+        let start = index * WORD_SIZE;
+        self.get_bits_unchecked(start, WORD_SIZE.min(self.len() - start))
+    }
 }
 
 impl<Backing: BitModify> BitModify for BitSlice<Backing> {
@@ -49,6 +94,18 @@ impl<Backing: BitModify> BitModify for BitSlice<Backing> {
         }
         unsafe { self.flip_bit_unchecked(index) }
     }
+
+    #[inline]
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        self.backing.set_bits_unchecked(self.start + index, len, value)
+    }
+
+    #[inline]
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        self.check_bits_range(index, len);
+        check_bits_value(len, value);
+        unsafe { self.set_bits_unchecked(index, len, value) }
+    }
 }
 
 impl<Backing: BitGet> Iterator for Iter<Backing> {
@@ -93,7 +150,7 @@ impl<'a, Backing: BitGet> IntoIterator for &'a BitSlice<Backing> {
 #[cfg(test)]
 mod test {
     use crate::bit_vec::BitVec;
-    use super::{BitGet, BitModify};
+    use super::{BitGet, BitModify, WordAccess};
 
     #[test]
     fn full_range_test() {
@@ -298,6 +355,61 @@ mod test {
         }
     }
 
+    #[test]
+    fn bits_test() {
+        let mut bv = BitVec::new(200);
+        let mut slice = bv.slice_mut(30..150);
+
+        slice.set_bits(20, 30, 0x2aaa_aaaa);
+        assert_eq!(0x2aaa_aaaa, slice.get_bits(20, 30));
+        slice.set_bits(56, 64, 0x0123_4567_89ab_cdef);
+        assert_eq!(0x0123_4567_89ab_cdef, slice.get_bits(56, 64));
+
+        for i in 0..bv.len() {
+            let expected = match i {
+                50..=79 => (i - 50) % 2 == 1,
+                86..=149 => (0x0123_4567_89ab_cdefu64 >> (i - 86)) & 1 == 1,
+                _ => false,
+            };
+            assert_eq!(expected, bv.get_bit(i), "incorrect value at index {i}");
+        }
+
+        let slice = bv.slice(86..150);
+        assert_eq!(0x0123_4567_89ab_cdef, slice.get_bits(0, 64));
+        assert_eq!(0x0123_4567, slice.get_bits(32, 32));
+    }
+
+    #[test]
+    fn word_access_test() {
+        let bv: BitVec = (0..200).map(|i| i % 3 == 0).collect();
+        let slice = bv.slice(5..150);
+        assert_eq!(145, slice.bit_len());
+        assert_eq!(3, slice.word_len());
+        for i in 0..slice.word_len() {
+            let word = slice.get_word(i);
+            for j in 0..64 {
+                let expected = i * 64 + j < slice.len() && (i * 64 + j + 5) % 3 == 0;
+                assert_eq!(expected, (word >> j) & 1 == 1, "incorrect bit {j} in word {i}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_bits_out_of_bounds_test() {
+        let bv = BitVec::new(200);
+        let slice = bv.slice(30..150);
+        slice.get_bits(100, 21);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_bits_out_of_bounds_test() {
+        let mut bv = BitVec::new(200);
+        let mut slice = bv.slice_mut(30..150);
+        slice.set_bits(100, 21, 0);
+    }
+
     #[test]
     fn flip_test() {
         let mut bv = BitVec::new(80);
@@ -1,4 +1,9 @@
-use std::rc::Rc;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+use super::{Word, WORD_SIZE};
 
 /// Defines methods for accessing bits stored in a datastructure.
 pub trait BitGet {
@@ -23,6 +28,60 @@ pub trait BitGet {
     ///
     /// returns: `true` if the index is a 1, `false` otherwise.
     fn get_bit(&self, index: usize) -> bool;
+
+    /// Get multiple consecutive bits at once without checking for bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the first bit to read.
+    /// * `len`: The number of bits to read.
+    ///
+    /// returns: The bits in the range as an integer, where the bit at `index` is the least
+    /// significant bit.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be at most the word size and all indices in `index..index + len` must be in
+    /// bounds.
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        let mut value = 0;
+        for i in (0..len).rev() {
+            value = (value << 1) | self.get_bit_unchecked(index + i) as Word;
+        }
+        value
+    }
+
+    /// Get multiple consecutive bits at once while checking for bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the first bit to read.
+    /// * `len`: The number of bits to read. This must be at most the word size.
+    ///
+    /// returns: The bits in the range as an integer, where the bit at `index` is the least
+    /// significant bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitModify, BitVec};
+    ///
+    /// let mut bv = BitVec::new(128);
+    /// bv.set_bit(62, true);
+    /// bv.set_bit(65, true);
+    ///
+    /// assert_eq!(0b1001, bv.get_bits(62, 4));
+    /// ```
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        if len > WORD_SIZE {
+            panic!("length is {len} but at most {WORD_SIZE} bits can be read at once")
+        }
+        let mut value = 0;
+        for i in (0..len).rev() {
+            value = (value << 1) | self.get_bit(index + i) as Word;
+        }
+        value
+    }
 }
 
 impl<T: BitGet + ?Sized> BitGet for &'_ T {
@@ -36,6 +95,16 @@ impl<T: BitGet + ?Sized> BitGet for &'_ T {
     fn get_bit(&self, index: usize) -> bool {
         <T as BitGet>::get_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits_unchecked(self, index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits(self, index, len)
+    }
 }
 
 impl<T: BitGet + ?Sized> BitGet for &'_ mut T {
@@ -48,6 +117,16 @@ impl<T: BitGet + ?Sized> BitGet for &'_ mut T {
     fn get_bit(&self, index: usize) -> bool {
         <T as BitGet>::get_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits_unchecked(self, index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits(self, index, len)
+    }
 }
 
 impl<T: BitModify + ?Sized> BitModify for &'_ mut T {
@@ -70,6 +149,16 @@ impl<T: BitModify + ?Sized> BitModify for &'_ mut T {
     fn flip_bit(&mut self, index: usize) {
         <T as BitModify>::flip_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        <T as BitModify>::set_bits_unchecked(self, index, len, value)
+    }
+
+    #[inline]
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        <T as BitModify>::set_bits(self, index, len, value)
+    }
 }
 
 impl<T: BitGet + ?Sized> BitGet for Box<T> {
@@ -83,6 +172,16 @@ impl<T: BitGet + ?Sized> BitGet for Box<T> {
     fn get_bit(&self, index: usize) -> bool {
         <T as BitGet>::get_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits_unchecked(self, index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits(self, index, len)
+    }
 }
 
 impl<T: BitModify + ?Sized> BitModify for Box<T> {
@@ -105,6 +204,16 @@ impl<T: BitModify + ?Sized> BitModify for Box<T> {
     fn flip_bit(&mut self, index: usize) {
         <T as BitModify>::flip_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        <T as BitModify>::set_bits_unchecked(self, index, len, value)
+    }
+
+    #[inline]
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        <T as BitModify>::set_bits(self, index, len, value)
+    }
 }
 
 impl<T: BitGet> BitGet for Rc<T> {
@@ -118,6 +227,16 @@ impl<T: BitGet> BitGet for Rc<T> {
     fn get_bit(&self, index: usize) -> bool {
         <T as BitGet>::get_bit(self, index)
     }
+
+    #[inline]
+    unsafe fn get_bits_unchecked(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits_unchecked(self, index, len)
+    }
+
+    #[inline]
+    fn get_bits(&self, index: usize, len: usize) -> Word {
+        <T as BitGet>::get_bits(self, index, len)
+    }
 }
 
 /// Defines methods for modifying bits stored in a datastructure.
@@ -159,7 +278,154 @@ pub trait BitModify {
     ///
     /// * `index`: The index of the bit to flip.
     fn flip_bit(&mut self, index: usize);
+
+    /// Sets multiple consecutive bits at once without checking for bounds. Bits of `value` which
+    /// do not fit into `len` bits are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the first bit to write.
+    /// * `len`: The number of bits to write.
+    /// * `value`: The bits to write, where the least significant bit is written to `index`.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be at most the word size and all indices in `index..index + len` must be in
+    /// bounds.
+    unsafe fn set_bits_unchecked(&mut self, index: usize, len: usize, value: Word) {
+        for i in 0..len {
+            self.set_bit_unchecked(index + i, (value >> i) & 1 == 1);
+        }
+    }
+
+    /// Sets multiple consecutive bits at once while checking for bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the first bit to write.
+    /// * `len`: The number of bits to write. This must be at most the word size.
+    /// * `value`: The bits to write, where the least significant bit is written to `index`. This
+    ///   must fit into `len` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitGet, BitModify, BitVec};
+    ///
+    /// let mut bv = BitVec::new(128);
+    /// bv.set_bits(60, 8, 0b1011_0001);
+    ///
+    /// assert_eq!(true, bv.get_bit(60));
+    /// assert_eq!(false, bv.get_bit(61));
+    /// assert_eq!(true, bv.get_bit(64));
+    /// assert_eq!(0b1011_0001, bv.get_bits(60, 8));
+    /// ```
+    fn set_bits(&mut self, index: usize, len: usize, value: Word) {
+        check_bits_value(len, value);
+        for i in 0..len {
+            self.set_bit(index + i, (value >> i) & 1 == 1);
+        }
+    }
+}
+
+/// Panics if `len` exceeds the word size or `value` does not fit into `len` bits.
+#[inline]
+pub(crate) fn check_bits_value(len: usize, value: Word) {
+    if len > WORD_SIZE {
+        panic!("length is {len} but at most {WORD_SIZE} bits can be written at once")
+    }
+    if len < WORD_SIZE && value >> len != 0 {
+        panic!("value {value} too large for {len}-bit integer")
+    }
 }
 
 pub trait BitAccess: BitGet + BitModify {}
 impl<T> BitAccess for T where T: BitGet + BitModify {}
+
+/// Defines methods for reading bits stored in a datastructure a word at a time.
+///
+/// Word `i` contains the bits at the indices `64 * i` to `64 * i + 63`, where the bit with the
+/// lowest index is the least significant bit of the word. Bits which lie beyond the end of the
+/// datastructure are always zero.
+pub trait WordAccess {
+    /// The number of bits stored in this datastructure.
+    fn bit_len(&self) -> usize;
+
+    /// The number of words needed to hold all bits of this datastructure.
+    #[inline]
+    fn word_len(&self) -> usize {
+        self.bit_len().div_ceil(WORD_SIZE)
+    }
+
+    /// Gets a word without checking for bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the word to read.
+    ///
+    /// # Safety
+    ///
+    /// The index must be less than [`WordAccess::word_len`].
+    unsafe fn get_word_unchecked(&self, index: usize) -> Word;
+
+    /// Gets a word while checking for bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The index of the word to read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::bit_vec::{BitModify, BitVec, WordAccess};
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.set_bit(66, true);
+    ///
+    /// assert_eq!(0b100, bv.get_word(1));
+    /// // Words of slices start at the slice's first bit
+    /// assert_eq!(0b1, bv.slice(66..).get_word(0));
+    /// ```
+    #[inline]
+    fn get_word(&self, index: usize) -> Word {
+        if index >= self.word_len() {
+            panic!("index is {index} but length is {}", self.word_len())
+        }
+        // SAFETY: We checked the index is in bounds
+        unsafe { self.get_word_unchecked(index) }
+    }
+}
+
+macro_rules! forward_word_access {
+    {$($tp:ty),+} => {
+        $(
+            impl<T: WordAccess + ?Sized> WordAccess for $tp {
+                #[inline]
+                fn bit_len(&self) -> usize {
+                    <T as WordAccess>::bit_len(self)
+                }
+
+                #[inline]
+                fn word_len(&self) -> usize {
+                    <T as WordAccess>::word_len(self)
+                }
+
+                #[inline]
+                unsafe fn get_word_unchecked(&self, index: usize) -> Word {
+                    <T as WordAccess>::get_word_unchecked(self, index)
+                }
+
+                #[inline]
+                fn get_word(&self, index: usize) -> Word {
+                    <T as WordAccess>::get_word(self, index)
+                }
+            }
+        )+
+    };
+}
+
+forward_word_access! { &'_ T, &'_ mut T, Box<T>, Rc<T> }
+
+// Targets without atomic pointer operations do not provide `Arc`
+#[cfg(target_has_atomic = "ptr")]
+forward_word_access! { Arc<T> }
@@ -0,0 +1,378 @@
+use core::iter::FusedIterator;
+
+use crate::bit_vec::slice::ExactBitPositions;
+use crate::bit_vec::{BitGet, BitModify, BitVec};
+use crate::int_vec::{IntAccess, IntVec};
+use crate::rank_select::flat_popcount::{LinearSearch, SelectStrategy};
+use crate::rank_select::{FlatPopcount, SelectSupport};
+
+/// A monotone (i.e. non-decreasing) sequence of integers encoded using the Elias-Fano encoding.
+///
+/// Each integer is split into its lower `l` bits, which are stored in an [`IntVec`], and its
+/// remaining upper bits. The upper bits are stored in a bit vector by setting the bit at index
+/// `upper + i` for the `i`-th integer. This uses at most `2 + log(u/n)` bits per integer, where
+/// `u` is the largest integer and `n` is the number of integers.
+///
+/// Random access and the search for successors and predecessors use select queries on the upper
+/// bits, which are supported by a [`FlatPopcount`] using the select strategy `Strat`.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::elias_fano::EliasFano;
+///
+/// let ef: EliasFano = EliasFano::new(&[2, 3, 5, 7, 11, 13, 24]);
+///
+/// assert_eq!(7, ef.len());
+/// assert_eq!(5, ef.get(2));
+/// assert_eq!(Some(11), ef.successor(8));
+/// assert_eq!(Some(7), ef.predecessor(8));
+/// assert_eq!(vec![2, 3, 5, 7, 11, 13, 24], ef.iter().collect::<Vec<_>>());
+/// ```
+pub struct EliasFano<Strat = LinearSearch> {
+    /// The upper bits of each integer in unary coding
+    high_bits: FlatPopcount<BitVec, Strat>,
+    /// The lower bits of each integer. This is empty if `low_width` is zero.
+    low_bits: IntVec,
+    /// The number of lower bits stored for each integer
+    low_width: usize,
+    len: usize,
+}
+
+impl<Strat: SelectStrategy> EliasFano<Strat> {
+    /// Encodes a monotone sequence of integers.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The integers to encode. They must be sorted in non-decreasing order.
+    ///
+    /// # Panics
+    ///
+    /// If the integers are not sorted.
+    pub fn new(values: &[usize]) -> Self {
+        assert!(
+            values.windows(2).all(|w| w[0] <= w[1]),
+            "values must be sorted in non-decreasing order"
+        );
+        let len = values.len();
+        let max = values.last().copied().unwrap_or(0);
+        let low_width = max
+            .checked_div(len)
+            .and_then(usize::checked_ilog2)
+            .unwrap_or(0) as usize;
+
+        let mut high_bits = BitVec::new(len + (max >> low_width) + 1);
+        let mut low_bits = IntVec::with_capacity(low_width.max(1), len);
+        let low_mask = (1 << low_width) - 1;
+        for (i, &v) in values.iter().enumerate() {
+            high_bits.set_bit((v >> low_width) + i, true);
+            if low_width > 0 {
+                low_bits.push(v & low_mask);
+            }
+        }
+
+        Self {
+            high_bits: FlatPopcount::new(high_bits),
+            low_bits,
+            low_width,
+            len,
+        }
+    }
+
+    /// Gets the number of integers in this sequence.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this sequence contains no integers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the lower bits of the integer with the given index.
+    ///
+    /// # Safety
+    ///
+    /// The index must be in bounds.
+    #[inline]
+    unsafe fn low(&self, index: usize) -> usize {
+        if self.low_width == 0 {
+            0
+        } else {
+            self.low_bits.get_unchecked(index)
+        }
+    }
+
+    /// Gets the position of the bit representing the integer with the given index in the upper
+    /// bits.
+    #[inline]
+    fn high_position(&self, index: usize) -> usize {
+        SelectSupport::<true>::select(&self.high_bits, index).expect("index in bounds")
+    }
+
+    /// Gets the integer at an index without checking for bounds.
+    ///
+    /// # Safety
+    ///
+    /// The index must be in bounds.
+    pub unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let high = self.high_position(index) - index;
+        (high << self.low_width) | self.low(index)
+    }
+
+    /// Gets the integer at an index while checking for bounds.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn get(&self, index: usize) -> usize {
+        if index >= self.len {
+            panic!("length is {} but index is {index}", self.len)
+        }
+        unsafe { self.get_unchecked(index) }
+    }
+
+    /// Finds the smallest integer in this sequence which is greater than or equal to `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value whose successor to find.
+    ///
+    /// returns: The successor or `None` if all integers are smaller than `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::elias_fano::EliasFano;
+    ///
+    /// let ef: EliasFano = EliasFano::new(&[4, 8, 8, 100]);
+    ///
+    /// assert_eq!(Some(4), ef.successor(0));
+    /// assert_eq!(Some(8), ef.successor(8));
+    /// assert_eq!(Some(100), ef.successor(9));
+    /// assert_eq!(None, ef.successor(101));
+    /// ```
+    pub fn successor(&self, value: usize) -> Option<usize> {
+        let high = value >> self.low_width;
+        let bits = self.high_bits.backing();
+        // The number of zeroes in the upper bits is the largest upper part plus one
+        if high >= bits.len() - self.len {
+            return None;
+        }
+        // Find the start of the bucket with the same upper bits as the value
+        let mut position = match high {
+            0 => 0,
+            _ => SelectSupport::<false>::select(&self.high_bits, high - 1)? + 1,
+        };
+        let mut index = position - high;
+        // Search the bucket for the first integer that is large enough
+        while index < self.len && bits.get_bit(position) {
+            let current = (high << self.low_width) | unsafe { self.low(index) };
+            if current >= value {
+                return Some(current);
+            }
+            index += 1;
+            position += 1;
+        }
+        // All integers in the following buckets are larger
+        (index < self.len).then(|| unsafe { self.get_unchecked(index) })
+    }
+
+    /// Finds the largest integer in this sequence which is less than or equal to `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value whose predecessor to find.
+    ///
+    /// returns: The predecessor or `None` if all integers are larger than `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::elias_fano::EliasFano;
+    ///
+    /// let ef: EliasFano = EliasFano::new(&[4, 8, 8, 100]);
+    ///
+    /// assert_eq!(None, ef.predecessor(3));
+    /// assert_eq!(Some(4), ef.predecessor(7));
+    /// assert_eq!(Some(8), ef.predecessor(99));
+    /// assert_eq!(Some(100), ef.predecessor(usize::MAX));
+    /// ```
+    pub fn predecessor(&self, value: usize) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let high = value >> self.low_width;
+        let bits = self.high_bits.backing();
+        let max_high = bits.len() - self.len - 1;
+        if high > max_high {
+            return Some(unsafe { self.get_unchecked(self.len - 1) });
+        }
+        // Find the end of the bucket with the same upper bits as the value
+        let mut position = SelectSupport::<false>::select(&self.high_bits, high)?;
+        let mut index = position - high;
+        // Search the bucket backwards for the first integer that is small enough
+        while index > 0 && bits.get_bit(position - 1) {
+            let current = (high << self.low_width) | unsafe { self.low(index - 1) };
+            if current <= value {
+                return Some(current);
+            }
+            index -= 1;
+            position -= 1;
+        }
+        // All integers in the preceding buckets are smaller
+        (index > 0).then(|| unsafe { self.get_unchecked(index - 1) })
+    }
+
+    /// Gets an iterator over the integers in this sequence in ascending order.
+    pub fn iter(&self) -> Iter<'_, Strat> {
+        Iter {
+            ef: self,
+            ones: self.high_bits.iter_ones(),
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl<'a, Strat: SelectStrategy> IntoIterator for &'a EliasFano<Strat> {
+    type Item = usize;
+
+    type IntoIter = Iter<'a, Strat>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the integers in an [`EliasFano`] sequence.
+pub struct Iter<'a, Strat> {
+    ef: &'a EliasFano<Strat>,
+    /// The positions of the upper bits of all remaining integers
+    ones: ExactBitPositions<&'a BitVec, true>,
+    /// The index of the next integer returned from the front
+    front: usize,
+    /// The index after the next integer returned from the back
+    back: usize,
+}
+
+impl<Strat: SelectStrategy> Iterator for Iter<'_, Strat> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.ones.next()?;
+        let index = self.front;
+        self.front += 1;
+        let high = position - index;
+        Some((high << self.ef.low_width) | unsafe { self.ef.low(index) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ones.size_hint()
+    }
+}
+
+impl<Strat: SelectStrategy> DoubleEndedIterator for Iter<'_, Strat> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let position = self.ones.next_back()?;
+        self.back -= 1;
+        let index = self.back;
+        let high = position - index;
+        Some((high << self.ef.low_width) | unsafe { self.ef.low(index) })
+    }
+}
+
+impl<Strat: SelectStrategy> ExactSizeIterator for Iter<'_, Strat> {
+    fn len(&self) -> usize {
+        self.ones.len()
+    }
+}
+
+impl<Strat: SelectStrategy> FusedIterator for Iter<'_, Strat> {}
+
+#[cfg(test)]
+mod test {
+    use crate::rank_select::flat_popcount::{BinarySearch, LinearSearch};
+
+    use super::EliasFano;
+
+    fn values() -> Vec<usize> {
+        let mut values = (0..5000).map(|i| (i * i) % 100_003 + i / 10).collect::<Vec<_>>();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn get_test() {
+        let values = values();
+        let ef = EliasFano::<LinearSearch>::new(&values);
+        assert_eq!(values.len(), ef.len());
+        assert!(!ef.is_empty());
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(v, ef.get(i), "value at index {i} incorrect");
+        }
+    }
+
+    #[test]
+    fn iter_test() {
+        let values = values();
+        let ef = EliasFano::<LinearSearch>::new(&values);
+        assert_eq!(values, ef.iter().collect::<Vec<_>>());
+        assert_eq!(values.len(), ef.iter().len());
+
+        let reversed = ef.iter().rev().collect::<Vec<_>>();
+        assert_eq!(values.iter().rev().copied().collect::<Vec<_>>(), reversed);
+
+        let mut iter = ef.iter();
+        assert_eq!(Some(values[0]), iter.next());
+        assert_eq!(Some(values[values.len() - 1]), iter.next_back());
+        assert_eq!(values.len() - 2, iter.len());
+    }
+
+    #[test]
+    fn successor_predecessor_test() {
+        let values = values();
+        let ef = EliasFano::<BinarySearch>::new(&values);
+        let max = values[values.len() - 1];
+        for x in (0..max + 10).step_by(7).chain([0, max, max + 1, usize::MAX]) {
+            let succ = values.iter().copied().find(|&v| v >= x);
+            let pred = values.iter().copied().rev().find(|&v| v <= x);
+            assert_eq!(succ, ef.successor(x), "successor of {x} incorrect");
+            assert_eq!(pred, ef.predecessor(x), "predecessor of {x} incorrect");
+        }
+    }
+
+    #[test]
+    fn small_universe_test() {
+        // More integers than the largest integer, so no lower bits are stored
+        let values = [0, 0, 1, 1, 1, 2, 5, 5, 7, 9, 9, 9];
+        let ef = EliasFano::<LinearSearch>::new(&values);
+        assert_eq!(values.to_vec(), ef.iter().collect::<Vec<_>>());
+        assert_eq!(Some(5), ef.successor(3));
+        assert_eq!(Some(2), ef.predecessor(4));
+        assert_eq!(None, ef.successor(10));
+    }
+
+    #[test]
+    fn empty_test() {
+        let ef = EliasFano::<LinearSearch>::new(&[]);
+        assert!(ef.is_empty());
+        assert_eq!(None, ef.iter().next());
+        assert_eq!(None, ef.successor(0));
+        assert_eq!(None, ef.predecessor(usize::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsorted_test() {
+        EliasFano::<LinearSearch>::new(&[1, 3, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds_test() {
+        EliasFano::<LinearSearch>::new(&[1, 3]).get(2);
+    }
+}
@@ -0,0 +1,349 @@
+use alloc::vec::Vec;
+
+use crate::bit_vec::{Word, WORD_SIZE};
+
+use super::IntAccess;
+
+/// A vector of bit-packed integers whose width of `W` bits is known at compile time.
+///
+/// This behaves like an [`IntVec`](super::IntVec) with a fixed width. Since the width is a
+/// constant, the masks are computed at compile time, and for widths dividing the word size, the
+/// case of an integer spanning two words is removed entirely. `W` must be between 1 and the number
+/// of bits in a `usize`.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::int_vec::{FixedIntVec, IntAccess};
+///
+/// let mut v = FixedIntVec::<12>::new();
+/// v.push(4095);
+/// v.push(7);
+/// v.set(1, 42);
+///
+/// assert_eq!(4095, v.get(0));
+/// assert_eq!(42, v.get(1));
+/// assert_eq!(12, v.width());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedIntVec<const W: usize> {
+    data: Vec<Word>,
+    size: usize,
+}
+
+impl<const W: usize> FixedIntVec<W> {
+    /// The mask containing the lowest `W` bits.
+    const MASK: Word = Word::MAX >> (WORD_SIZE - W);
+
+    /// Whether an integer may span two words, which is only possible if `W` does not divide the
+    /// word size.
+    const MAY_SPAN_WORDS: bool = !WORD_SIZE.is_multiple_of(W);
+
+    /// Creates an empty vector.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty vector with space for at least `capacity` integers.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        const {
+            assert!(
+                0 < W && W <= usize::BITS as usize,
+                "width must be between 1 and the number of bits in a usize"
+            )
+        }
+        Self {
+            data: Vec::with_capacity((capacity * W).div_ceil(WORD_SIZE)),
+            size: 0,
+        }
+    }
+
+    /// Gets the number of bits used to store each integer.
+    #[inline]
+    pub const fn width(&self) -> usize {
+        W
+    }
+
+    /// Gets the number of integers stored in this vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Gets the number of integers this vector can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.capacity() * WORD_SIZE / W
+    }
+
+    #[inline]
+    pub fn raw_data(&self) -> &[Word] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn iter(&self) -> FixedIter<'_, W> {
+        FixedIter { i: 0, v: self }
+    }
+
+    /// Appends an integer to the back of the vector.
+    ///
+    /// # Panics
+    ///
+    /// If the value does not fit into `W` bits.
+    pub fn push(&mut self, value: usize) {
+        Self::check_value(value);
+        if (self.size + 1) * W > self.data.len() * WORD_SIZE {
+            self.data.push(0);
+        }
+        self.size += 1;
+        // SAFETY: The index is in bounds and the value fits
+        unsafe { self.set_unchecked(self.size - 1, value) }
+    }
+
+    /// Removes the last integer and returns it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<usize> {
+        let last = self.size.checked_sub(1)?;
+        // SAFETY: The vector is not empty
+        let value = unsafe { self.get_unchecked(last) };
+        self.truncate(last);
+        Some(value)
+    }
+
+    /// Shortens the vector to the given length. This has no effect if the vector is already
+    /// shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.size {
+            return;
+        }
+        let bits = len * W;
+        self.data.truncate(bits.div_ceil(WORD_SIZE));
+        if let Some(last) = self.data.last_mut() {
+            // Bits past the end are always zero
+            *last &= Word::MAX >> ((WORD_SIZE - bits % WORD_SIZE) % WORD_SIZE);
+        }
+        self.size = len;
+    }
+
+    /// Removes all integers from the vector.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Panics if the value does not fit into `W` bits.
+    #[inline]
+    fn check_value(value: usize) {
+        if value as Word & !Self::MASK != 0 {
+            panic!("value {value} too large for {W}-bit integer")
+        }
+    }
+}
+
+impl<const W: usize> Default for FixedIntVec<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize> IntAccess for FixedIntVec<W> {
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let (block, offset) = (index * W / WORD_SIZE, index * W % WORD_SIZE);
+        let mut value = *self.data.get_unchecked(block) >> offset;
+        if Self::MAY_SPAN_WORDS && offset + W > WORD_SIZE {
+            value |= *self.data.get_unchecked(block + 1) << (WORD_SIZE - offset);
+        }
+        (value & Self::MASK) as usize
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> usize {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        // SAFETY: We checked the index is in bounds
+        unsafe { self.get_unchecked(index) }
+    }
+
+    #[inline]
+    unsafe fn set_unchecked(&mut self, index: usize, value: usize) {
+        let value = value as Word & Self::MASK;
+        let (block, offset) = (index * W / WORD_SIZE, index * W % WORD_SIZE);
+        let low = self.data.get_unchecked_mut(block);
+        *low = *low & !(Self::MASK << offset) | value << offset;
+        if Self::MAY_SPAN_WORDS && offset + W > WORD_SIZE {
+            let shift = WORD_SIZE - offset;
+            let high = self.data.get_unchecked_mut(block + 1);
+            *high = *high & !(Self::MASK >> shift) | value >> shift;
+        }
+    }
+
+    #[inline]
+    fn set(&mut self, index: usize, value: usize) {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        Self::check_value(value);
+        // SAFETY: We checked the index is in bounds and the value fits
+        unsafe { self.set_unchecked(index, value) }
+    }
+}
+
+impl<const W: usize> Extend<usize> for FixedIntVec<W> {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.data
+            .reserve(((self.size + iter.size_hint().0) * W).div_ceil(WORD_SIZE) - self.data.len());
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<const W: usize> FromIterator<usize> for FixedIntVec<W> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<'a, const W: usize> IntoIterator for &'a FixedIntVec<W> {
+    type Item = usize;
+
+    type IntoIter = FixedIter<'a, W>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the integers in a [`FixedIntVec`].
+pub struct FixedIter<'a, const W: usize> {
+    i: usize,
+    v: &'a FixedIntVec<W>,
+}
+
+impl<const W: usize> Iterator for FixedIter<'_, W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i == self.v.len() {
+            return None;
+        }
+
+        // SAFETY: The index is in bounds
+        let res = unsafe { self.v.get_unchecked(self.i) };
+        self.i += 1;
+        Some(res)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<const W: usize> ExactSizeIterator for FixedIter<'_, W> {
+    fn len(&self) -> usize {
+        self.v.len() - self.i
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::FixedIntVec;
+    use crate::int_vec::{IntAccess, IntVec};
+
+    /// Checks that a fixed-width vector behaves exactly like an `IntVec` with the same width.
+    fn check<const W: usize>() {
+        let mask = usize::MAX >> (usize::BITS as usize - W);
+        let values: Vec<usize> = (0..300usize)
+            .map(|i| (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) as usize & mask)
+            .collect();
+
+        let mut fixed = FixedIntVec::<W>::new();
+        let mut expected = IntVec::new(W.min(63));
+        // An `IntVec` can store at most 63 bits per integer
+        for &value in values.iter().filter(|&&v| v as u64 >> 63 == 0) {
+            fixed.push(value);
+            expected.push(value);
+        }
+        assert!(fixed.iter().eq(expected.iter()), "width {W}");
+
+        for i in (0..fixed.len()).step_by(7) {
+            fixed.set(i, i & mask);
+            expected.set(i, i & mask);
+        }
+        assert!(fixed.iter().eq(expected.iter()), "width {W}");
+        assert!((0..fixed.len()).all(|i| fixed.get(i) == expected.get(i)));
+
+        fixed.truncate(101);
+        assert_eq!(Some(expected.get(100)), fixed.pop());
+        assert_eq!(100, fixed.len());
+        assert_eq!((100 * W).div_ceil(64), fixed.raw_data().len());
+        // Bits past the end must have been cleared, so pushing still works
+        fixed.push(mask);
+        assert_eq!(mask, fixed.get(100));
+
+        let collected: FixedIntVec<W> = fixed.iter().collect();
+        assert_eq!(fixed, collected);
+    }
+
+    #[test]
+    fn widths_test() {
+        check::<1>();
+        check::<7>();
+        check::<12>();
+        check::<32>();
+        #[cfg(target_pointer_width = "64")]
+        {
+            check::<40>();
+            check::<63>();
+            check::<64>();
+        }
+    }
+
+    #[test]
+    fn empty_test() {
+        let mut v = FixedIntVec::<5>::default();
+        assert!(v.is_empty());
+        assert_eq!(None, v.pop());
+        assert_eq!(0, v.iter().len());
+
+        v.extend([1, 2, 3]);
+        v.clear();
+        assert!(v.is_empty());
+        assert!(v.raw_data().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_too_large_number_test() {
+        let mut v = FixedIntVec::<7>::new();
+        v.push(128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_too_large_number_test() {
+        let mut v = FixedIntVec::<7>::new();
+        v.push(0);
+        v.set(0, 128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds_test() {
+        let v: FixedIntVec<7> = [1, 2].into_iter().collect();
+        v.get(2);
+    }
+}
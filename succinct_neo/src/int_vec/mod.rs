@@ -1,15 +1,34 @@
-use core::panic;
-
-pub use traits::IntAccess;
-
+use alloc::{vec, vec::Vec};
+use core::ops::{Bound, Range, RangeBounds};
+use core::{mem, panic};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::bit_vec::{slice::BitSlice, BitGet, BitModify, Word};
+use crate::traits::BlockType;
+use crate::serialize::{self, ReadBytes, SerializeError};
+
+pub use fixed::{FixedIntVec, FixedIter};
+pub use signed::{SignedIntVec, SignedIter};
+pub use traits::{IntAccess, SignedIntAccess};
+
+mod fixed;
+mod packing;
+mod signed;
 mod traits;
 
+/// The magic number identifying a serialized [`IntVec`]
+const INT_VEC_MAGIC: [u8; 4] = *b"SNIV";
+
 #[derive(Debug)]
 pub struct IntVec {
-    data: Vec<usize>,
+    data: Vec<Word>,
     width: usize,
     capacity: usize,
     size: usize,
+    /// Whether adding a value which does not fit the width widens all integers instead of
+    /// panicking
+    growing: bool,
 }
 
 impl IntVec {
@@ -28,19 +47,44 @@ impl IntVec {
             width,
             capacity: num_blocks * block_size / width,
             size: 0,
+            growing: false,
         };
 
         temp.data.push(0);
         temp
     }
 
+    /// Creates a vector containing the given integers, using the smallest width that fits all of
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// If an integer needs the full 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::{IntAccess, IntVec};
+    ///
+    /// let v = IntVec::from_slice_min_width(&[3, 1000, 7]);
+    ///
+    /// assert_eq!(10, v.width());
+    /// assert_eq!(1000, v.get(1));
+    /// ```
+    pub fn from_slice_min_width(values: &[usize]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let mut v = Self::with_capacity(Self::min_width(max), values.len());
+        v.extend_from_slice(values);
+        v
+    }
+
     #[inline]
     const fn block_width() -> usize {
-        std::mem::size_of::<usize>() * 8
+        Word::BITS as usize
     }
 
     #[inline]
-    const fn mask(&self) -> usize {
+    const fn mask(&self) -> Word {
         (1 << self.width) - 1
     }
 
@@ -52,9 +96,8 @@ impl IntVec {
     }
 
     pub fn push(&mut self, v: usize) {
-        if v >= (1 << self.width) {
-            panic!("value too large for {}-bit integer", self.width)
-        }
+        self.fit_value(v);
+        let v = v as Word;
         let offset = self.current_offset();
         let mask = self.mask();
         if offset == 0 {
@@ -80,8 +123,65 @@ impl IntVec {
         self.size += 1;
     }
 
+    /// Gets the number of bits used to store each integer.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns whether adding an integer which does not fit the width widens all integers instead
+    /// of panicking.
+    #[inline]
+    pub fn is_growing(&self) -> bool {
+        self.growing
+    }
+
+    /// Sets whether adding an integer which does not fit the width widens all integers instead of
+    /// panicking. In this mode, [`IntVec::push`], [`IntAccess::set`] and all other methods adding
+    /// integers re-pack the vector to the smallest width fitting the new integer if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::{IntAccess, IntVec};
+    ///
+    /// let mut v = IntVec::new(2);
+    /// v.set_growing(true);
+    /// v.push(3);
+    /// v.push(100);
+    ///
+    /// assert_eq!(7, v.width());
+    /// assert_eq!(vec![3, 100], v.iter().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn set_growing(&mut self, growing: bool) {
+        self.growing = growing;
+    }
+
+    /// Re-packs all integers to the smallest width that fits the current contents. The integers
+    /// are moved in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v = IntVec::new(32);
+    /// v.extend_from_slice(&[5, 17, 2]);
+    /// v.shrink_width();
+    ///
+    /// assert_eq!(5, v.width());
+    /// assert_eq!(vec![5, 17, 2], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn shrink_width(&mut self) {
+        let width = Self::min_width(self.iter().max().unwrap_or(0));
+        if width < self.width {
+            self.repack(width);
+        }
+    }
+
     #[inline]
-    pub fn raw_data(&self) -> &[usize] {
+    pub fn raw_data(&self) -> &[Word] {
         &self.data
     }
 
@@ -90,15 +190,579 @@ impl IntVec {
         self.size == 0
     }
 
+    /// Gets the number of integers stored in this vector.
     #[inline]
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.size
     }
 
     #[inline]
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter { i: 0, v: self }
     }
+
+    /// Decodes a range of integers into a slice. Instead of extracting the integers one by one,
+    /// they are unpacked in bulk, using SIMD instructions if the CPU supports them.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of indices of the integers to decode.
+    /// * `out` - The slice to write the integers to. It must have the same length as the range.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, the slice's length differs from the range's length or the
+    /// width of the integers exceeds the number of bits in `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let v: IntVec = (0..100).collect();
+    /// let mut out = [0u32; 5];
+    /// v.decode_range(20..25, &mut out);
+    ///
+    /// assert_eq!([20, 21, 22, 23, 24], out);
+    /// ```
+    pub fn decode_range<T: BlockType>(&self, range: impl RangeBounds<usize>, out: &mut [T]) {
+        let start = match range.start_bound() {
+            Bound::Excluded(&s) => s.checked_add(1).unwrap_or_else(|| {
+                panic!("range starts after {s} but length is {}", self.len())
+            }),
+            Bound::Included(&s) => s,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Excluded(&e) => e,
+            Bound::Included(&e) => e.checked_add(1).unwrap_or_else(|| {
+                panic!("range is {start}..={e} but length is {}", self.len())
+            }),
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            panic!("range is {start}..{end} but length is {}", self.len())
+        }
+        if out.len() != end - start {
+            panic!("range length is {} but output length is {}", end - start, out.len())
+        }
+        if self.width > T::BITS {
+            panic!("{}-bit integers do not fit into {}-bit outputs", self.width, T::BITS)
+        }
+        packing::unpack(&self.data, self.width, start, out);
+    }
+
+    /// Appends all integers of a slice to the vector. Unlike [`IntVec::extend_from_slice`], the
+    /// integers are packed a word at a time.
+    ///
+    /// # Panics
+    ///
+    /// If any of the values does not fit the width and the vector is not in growing mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::{IntAccess, IntVec};
+    ///
+    /// let mut v = IntVec::new(10);
+    /// v.encode_from(&[1, 1000, 7]);
+    ///
+    /// assert_eq!(3, v.len());
+    /// assert_eq!(1000, v.get(1));
+    /// ```
+    pub fn encode_from(&mut self, values: &[u64]) {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let max = usize::try_from(max)
+            .unwrap_or_else(|_| panic!("value {max} too large for an integer vector"));
+        self.fit_value(max);
+        let len = self.len();
+        self.set_len(len + values.len());
+        packing::pack(&mut self.data, self.width, len, values);
+    }
+
+    /// Gets an iterator decoding this vector in chunks of `chunk_len` integers, using
+    /// [`IntVec::decode_range`]. The last chunk may be shorter.
+    ///
+    /// The iterator allocates a new vector for every chunk. To avoid this,
+    /// [`DecodeChunks::next_chunk`] decodes every chunk into the same buffer instead.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_len` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let v: IntVec = (0..10).collect();
+    /// let sums: Vec<u64> = v.decode_chunks(4).map(|chunk| chunk.iter().sum()).collect();
+    ///
+    /// assert_eq!(vec![6, 22, 17], sums);
+    /// ```
+    pub fn decode_chunks(&self, chunk_len: usize) -> DecodeChunks<'_> {
+        if chunk_len == 0 {
+            panic!("chunk length must not be 0")
+        }
+        DecodeChunks {
+            i: 0,
+            chunk_len,
+            buf: Vec::new(),
+            v: self,
+        }
+    }
+
+    /// Reserves space for at least `additional` more integers.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = ((self.size + additional) * self.width) / Self::block_width() + 1;
+        self.data.reserve(needed.saturating_sub(self.data.len()));
+        self.capacity = self.data.capacity() * Self::block_width() / self.width;
+    }
+
+    /// Removes the last integer and returns it, or `None` if the vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = [3, 1, 4].into_iter().collect();
+    ///
+    /// assert_eq!(Some(4), v.pop());
+    /// assert_eq!(2, v.len());
+    /// ```
+    pub fn pop(&mut self) -> Option<usize> {
+        let last = self.len().checked_sub(1)?;
+        // SAFETY: The vector is not empty
+        let value = unsafe { self.get_unchecked(last) };
+        self.set_len(last);
+        Some(value)
+    }
+
+    /// Inserts an integer at the given index, moving all following integers one position to the
+    /// back. The packed integers are moved a word at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index to insert the integer at. This may be equal to the length.
+    /// * `value` - The integer to insert.
+    ///
+    /// # Panics
+    ///
+    /// If the index is greater than the length or the value does not fit the width and the vector
+    /// is not in growing mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = [3, 1, 4].into_iter().collect();
+    /// v.insert(1, 5);
+    ///
+    /// assert_eq!(vec![3, 5, 1, 4], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn insert(&mut self, index: usize, value: usize) {
+        let len = self.len();
+        if index > len {
+            panic!("length is {len} but index is {index}")
+        }
+        self.fit_value(value);
+        let width = self.width;
+        self.set_len(len + 1);
+        self.bits_mut()
+            .copy_within(index * width..len * width, (index + 1) * width);
+        // SAFETY: The index is in bounds and the value fits
+        unsafe { self.set_unchecked(index, value) }
+    }
+
+    /// Removes the integer at the given index and returns it, moving all following integers one
+    /// position to the front. The packed integers are moved a word at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the integer to remove.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> usize {
+        let value = self.get(index);
+        let (len, width) = (self.len(), self.width);
+        self.bits_mut()
+            .copy_within((index + 1) * width..len * width, index * width);
+        self.set_len(len - 1);
+        value
+    }
+
+    /// Removes the integer at the given index and returns it, replacing it with the last integer.
+    /// This does not preserve the order of the integers, but takes constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the integer to remove.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> usize {
+        let value = self.get(index);
+        let last = self.len() - 1;
+        // SAFETY: Both indices are in bounds
+        unsafe { self.set_unchecked(index, self.get_unchecked(last)) }
+        self.set_len(last);
+        value
+    }
+
+    /// Shortens the vector to the given length. This has no effect if the vector is already
+    /// shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.set_len(len);
+        }
+    }
+
+    /// Removes all integers from the vector.
+    pub fn clear(&mut self) {
+        self.set_len(0);
+    }
+
+    /// Changes the length of the vector. If it grows, the new integers are set to `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_len` - The new number of integers.
+    /// * `value` - The value of the added integers.
+    ///
+    /// # Panics
+    ///
+    /// If integers are added, the value does not fit the width and the vector is not in growing
+    /// mode.
+    pub fn resize(&mut self, new_len: usize, value: usize) {
+        let len = self.len();
+        if new_len <= len {
+            return self.truncate(new_len);
+        }
+        self.fit_value(value);
+        self.set_len(new_len);
+        if value != 0 {
+            // SAFETY: The indices are in bounds and the value fits
+            (len..new_len).for_each(|i| unsafe { self.set_unchecked(i, value) });
+        }
+    }
+
+    /// Appends all integers of a slice to the vector.
+    ///
+    /// # Panics
+    ///
+    /// If any of the values does not fit the width and the vector is not in growing mode.
+    pub fn extend_from_slice(&mut self, values: &[usize]) {
+        self.extend(values.iter().copied());
+    }
+
+    /// Keeps only the integers for which the predicate returns `true`, preserving their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = (0..10).collect();
+    /// v.retain(|x| x % 3 == 0);
+    ///
+    /// assert_eq!(vec![0, 3, 6, 9], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(usize) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len() {
+            // SAFETY: Both indices are in bounds since at most `i` integers were kept so far
+            unsafe {
+                let value = self.get_unchecked(i);
+                if f(value) {
+                    self.set_unchecked(kept, value);
+                    kept += 1;
+                }
+            }
+        }
+        self.set_len(kept);
+    }
+
+    /// Removes consecutive repeated integers, such that a sorted vector contains every integer
+    /// only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = [1, 1, 2, 3, 3, 3, 1].into_iter().collect();
+    /// v.dedup();
+    ///
+    /// assert_eq!(vec![1, 2, 3, 1], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn dedup(&mut self) {
+        let mut previous = None;
+        self.retain(|value| previous.replace(value) != Some(value));
+    }
+
+    /// Sorts the integers in ascending order using a merge sort. Sorted runs are copied a word at
+    /// a time, so this is fast for partially sorted data, but it needs a buffer as large as the
+    /// vector itself. Since equal integers cannot be told apart, the result is the same as for
+    /// [`IntVec::sort_unstable`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = [5, 1, 4, 1, 3].into_iter().collect();
+    /// v.sort();
+    ///
+    /// assert_eq!(vec![1, 1, 3, 4, 5], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn sort(&mut self) {
+        let len = self.len();
+        let mut buffer = Self::with_capacity(self.width, len);
+        buffer.set_len(len);
+        let mut run = 1;
+        while run < len {
+            for start in (0..len).step_by(2 * run) {
+                let mid = (start + run).min(len);
+                let end = (start + 2 * run).min(len);
+                // SAFETY: The ranges are in bounds of both vectors
+                unsafe { self.merge_into(&mut buffer, start, mid, end) }
+            }
+            mem::swap(&mut self.data, &mut buffer.data);
+            mem::swap(&mut self.capacity, &mut buffer.capacity);
+            run *= 2;
+        }
+    }
+
+    /// Merges the sorted ranges `start..mid` and `mid..end` of this vector into the range
+    /// `start..end` of another vector with the same width.
+    ///
+    /// # Safety
+    ///
+    /// The ranges must be in bounds of both vectors and `start < mid <= end`.
+    unsafe fn merge_into(&self, dest: &mut Self, start: usize, mid: usize, end: usize) {
+        let width = self.width;
+        if mid == end || self.get_unchecked(mid - 1) <= self.get_unchecked(mid) {
+            // The ranges are already in order, so they can be copied as a whole
+            dest.bits_mut()
+                .slice_unchecked_mut(start * width..end * width)
+                .copy_from_bitslice(&self.bits().slice_unchecked(start * width..end * width));
+            return;
+        }
+        let (mut left, mut right) = (start, mid);
+        for i in start..end {
+            let value = if right == end
+                || (left < mid && self.get_unchecked(left) <= self.get_unchecked(right))
+            {
+                left += 1;
+                self.get_unchecked(left - 1)
+            } else {
+                right += 1;
+                self.get_unchecked(right - 1)
+            };
+            dest.set_unchecked(i, value);
+        }
+    }
+
+    /// Sorts the integers in ascending order using a heap sort, which works in place without
+    /// allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let mut v: IntVec = [5, 1, 4, 1, 3].into_iter().collect();
+    /// v.sort_unstable();
+    ///
+    /// assert_eq!(vec![1, 1, 3, 4, 5], v.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn sort_unstable(&mut self) {
+        let len = self.len();
+        // SAFETY: All indices are in bounds
+        unsafe {
+            for i in (0..len / 2).rev() {
+                self.sift_down(i, len);
+            }
+            // Repeatedly move the maximum of the heap behind it
+            for end in (1..len).rev() {
+                let max = self.get_unchecked(0);
+                self.set_unchecked(0, self.get_unchecked(end));
+                self.set_unchecked(end, max);
+                self.sift_down(0, end);
+            }
+        }
+    }
+
+    /// Moves the integer at `index` down the max-heap formed by the first `len` integers until
+    /// the heap property is restored.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be at most the length of this vector and `index` must be less than `len`.
+    unsafe fn sift_down(&mut self, mut index: usize, len: usize) {
+        let value = self.get_unchecked(index);
+        loop {
+            let mut child = 2 * index + 1;
+            if child >= len {
+                break;
+            }
+            if child + 1 < len && self.get_unchecked(child + 1) > self.get_unchecked(child) {
+                child += 1;
+            }
+            let child_value = self.get_unchecked(child);
+            if child_value <= value {
+                break;
+            }
+            self.set_unchecked(index, child_value);
+            index = child;
+        }
+        self.set_unchecked(index, value);
+    }
+
+    /// Changes the number of integers in this vector. Blocks are added or removed such that the
+    /// block the next integer is pushed into exists, and all bits past the last integer are
+    /// cleared.
+    fn set_len(&mut self, len: usize) {
+        let bits = len * self.width;
+        self.data.resize(bits / Self::block_width() + 1, 0);
+        *self.data.last_mut().unwrap() &= (1 << (bits % Self::block_width())) - 1;
+        self.size = len;
+        self.capacity = self.data.capacity() * Self::block_width() / self.width;
+    }
+
+    /// Makes sure the value fits the width of this vector. If it does not, the vector is widened
+    /// in growing mode and this panics otherwise.
+    #[inline]
+    fn fit_value(&mut self, value: usize) {
+        if (value as Word) >> self.width == 0 {
+            return;
+        }
+        if !self.growing {
+            panic!("value {value} too large for {}-bit integer", self.width)
+        }
+        self.repack(Self::min_width(value));
+    }
+
+    /// Gets the smallest width which fits the given value.
+    fn min_width(value: usize) -> usize {
+        let width = ((usize::BITS - value.leading_zeros()) as usize).max(1);
+        if width >= Self::block_width() {
+            panic!("value {value} too large for an integer vector")
+        }
+        width
+    }
+
+    /// Changes the width of all integers, which must fit all of them. The integers are moved
+    /// starting at the front when shrinking and at the back when widening, so that no integer is
+    /// overwritten before it is read.
+    fn repack(&mut self, width: usize) {
+        let (old_width, len) = (self.width, self.len());
+        if width > old_width {
+            self.data.resize((len * width) / Self::block_width() + 1, 0);
+        }
+        let data = &mut self.data;
+        // SAFETY: Every integer is in bounds for both widths
+        let move_int = |i: usize| unsafe {
+            let value = data.get_bits_unchecked(i * old_width, old_width);
+            data.set_bits_unchecked(i * width, width, value);
+        };
+        if width < old_width {
+            (0..len).for_each(move_int);
+        } else {
+            (0..len).rev().for_each(move_int);
+        }
+        self.width = width;
+        self.set_len(len);
+    }
+
+    /// Gets a view of the bits containing the packed integers.
+    #[inline]
+    fn bits(&self) -> BitSlice<&Vec<Word>> {
+        BitSlice::new(&self.data, 0, self.size * self.width)
+    }
+
+    /// Gets a mutable view of the bits containing the packed integers.
+    #[inline]
+    fn bits_mut(&mut self) -> BitSlice<&mut Vec<Word>> {
+        BitSlice::new(&mut self.data, 0, self.size * self.width)
+    }
+
+    /// Writes this vector in a binary format which can be read with [`IntVec::read_from`].
+    ///
+    /// The format consists of a header, the bit width, the number of integers and the words
+    /// containing the packed integers, all stored in little-endian byte order. See the
+    /// [`serialize`] module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::{IntAccess, IntVec};
+    ///
+    /// let mut v = IntVec::new(5);
+    /// v.push(21);
+    /// v.push(3);
+    ///
+    /// let mut buf = Vec::new();
+    /// v.write_to(&mut buf).unwrap();
+    ///
+    /// let read = IntVec::read_from(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(21, read.get(0));
+    /// assert_eq!(3, read.get(1));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        serialize::write_header(writer, INT_VEC_MAGIC)?;
+        serialize::write_u64(writer, self.width as u64)?;
+        serialize::write_u64(writer, self.size as u64)?;
+        let num_words = (self.size * self.width).div_ceil(Self::block_width());
+        serialize::write_words(writer, &self.data[..num_words])
+    }
+
+    /// Reads a vector written by [`IntVec::write_to`].
+    ///
+    /// returns: The vector or an error if reading failed or the data is malformed.
+    #[cfg(feature = "std")]
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, SerializeError> {
+        Self::read_bytes(reader)
+    }
+
+    /// Reads a vector written by [`IntVec::write_to`] from any source of bytes, which does not
+    /// require the `std` feature for byte slices.
+    pub(crate) fn read_bytes(reader: &mut impl ReadBytes) -> Result<Self, SerializeError> {
+        serialize::read_header(reader, INT_VEC_MAGIC)?;
+        let width = serialize::read_usize(reader)?;
+        // The integers must fit into a usize, which may be smaller than a word
+        if width == 0 || width >= Self::block_width() || width > usize::BITS as usize {
+            return Err(SerializeError::Invalid("unsupported integer width"));
+        }
+        let size = serialize::read_usize(reader)?;
+        let num_bits = size
+            .checked_mul(width)
+            .ok_or(SerializeError::Invalid("length too large"))?;
+        let mut data = serialize::read_words(reader, num_bits.div_ceil(Self::block_width()))?;
+
+        let rest_bits = num_bits % Self::block_width();
+        if rest_bits != 0 && data[data.len() - 1] >> rest_bits != 0 {
+            return Err(SerializeError::Invalid("bits past the end of the vector are set"));
+        }
+        // The block the next integer is pushed into always exists
+        if rest_bits == 0 {
+            data.push(0);
+        }
+
+        Ok(Self {
+            capacity: data.capacity() * Self::block_width() / width,
+            data,
+            width,
+            size,
+            growing: false,
+        })
+    }
 }
 
 impl IntAccess for IntVec {
@@ -121,26 +785,23 @@ impl IntAccess for IntVec {
             let lo = self.data[index_block] >> index_offset;
             let mask = (1 << remaining_bits) - 1;
             let hi = self.data[index_block + 1] & mask;
-            return (hi << fitting_bits) | lo;
+            return ((hi << fitting_bits) | lo) as usize;
         }
 
-        let mask = (1 << self.width) - 1;
-        (self.data[index_block] >> index_offset) & mask
+        ((self.data[index_block] >> index_offset) & self.mask()) as usize
     }
 
     fn set(&mut self, index: usize, value: usize) {
         if index >= self.len() {
             panic!("length is {} but index is {index}", self.len())
         }
-        if value >= (1 << self.width) {
-            panic!("value {value} too large for {}-bit integer", self.width)
-        }
+        self.fit_value(value);
         unsafe { self.set_unchecked(index, value) }
     }
 
     unsafe fn set_unchecked(&mut self, index: usize, value: usize) {
-        let mask = (1 << self.width) - 1;
-        let value = value & mask;
+        let mask = self.mask();
+        let value = value as Word & mask;
         let index_block = (index * self.width) / Self::block_width();
         let index_offset = (index * self.width) % Self::block_width();
 
@@ -163,6 +824,34 @@ impl IntAccess for IntVec {
     }
 }
 
+impl Extend<usize> for IntVec {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Collects integers into a vector using the smallest width that fits all of them.
+///
+/// The integers are pushed in growing mode starting at a width of 1, so they are re-packed at
+/// most once per bit of the final width. The resulting vector is not in growing mode.
+///
+/// # Panics
+///
+/// If an integer needs the full 64 bits.
+impl FromIterator<usize> for IntVec {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut v = Self::new(1);
+        v.set_growing(true);
+        v.extend(iter);
+        v.set_growing(false);
+        v
+    }
+}
+
 impl IntoIterator for IntVec {
     type Item = usize;
 
@@ -233,9 +922,86 @@ impl ExactSizeIterator for Iter<'_> {
     }
 }
 
+/// An iterator over the integers of an [`IntVec`], decoded in chunks.
+///
+/// This is created by [`IntVec::decode_chunks`]. Every item is a newly allocated vector.
+/// [`DecodeChunks::next_chunk`] decodes into a buffer that is reused for every chunk instead.
+pub struct DecodeChunks<'a> {
+    i: usize,
+    chunk_len: usize,
+    buf: Vec<u64>,
+    v: &'a IntVec,
+}
+
+impl DecodeChunks<'_> {
+    /// Decodes the next chunk into a buffer owned by this iterator, which is only allocated once.
+    /// Chunks decoded this way are skipped by the iterator.
+    ///
+    /// returns: The decoded integers, or `None` if all integers have been decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::IntVec;
+    ///
+    /// let v: IntVec = (0..10).collect();
+    /// let mut chunks = v.decode_chunks(4);
+    /// let mut sums = Vec::new();
+    /// while let Some(chunk) = chunks.next_chunk() {
+    ///     sums.push(chunk.iter().sum::<u64>());
+    /// }
+    ///
+    /// assert_eq!(vec![6, 22, 17], sums);
+    /// ```
+    pub fn next_chunk(&mut self) -> Option<&[u64]> {
+        let range = self.next_range()?;
+        // This only allocates for the first chunk, since no chunk is longer
+        self.buf.resize(self.chunk_len.min(self.v.len()), 0);
+        let chunk = &mut self.buf[..range.len()];
+        self.v.decode_range(range, chunk);
+        Some(chunk)
+    }
+
+    /// Advances past the next chunk and returns its range of indices.
+    fn next_range(&mut self) -> Option<Range<usize>> {
+        if self.i == self.v.len() {
+            return None;
+        }
+        let start = self.i;
+        self.i = self.v.len().min(start + self.chunk_len);
+        Some(start..self.i)
+    }
+}
+
+impl Iterator for DecodeChunks<'_> {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = self.next_range()?;
+        let mut chunk = vec![0; range.len()];
+        self.v.decode_range(range, &mut chunk);
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl ExactSizeIterator for DecodeChunks<'_> {
+    fn len(&self) -> usize {
+        (self.v.len() - self.i).div_ceil(self.chunk_len)
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use core::ops::Bound;
+
     use super::{traits::IntAccess, IntVec};
+    #[cfg(feature = "std")]
+    use crate::serialize::SerializeError;
+    use crate::test_util::Rng;
 
     #[test]
     fn basics_test() {
@@ -276,7 +1042,7 @@ mod test {
             v.push(1);
         }
 
-        for (expected, actual) in std::iter::repeat(1).zip(&v) {
+        for (expected, actual) in core::iter::repeat(1).zip(&v) {
             assert_eq!(expected, actual)
         }
 
@@ -368,4 +1134,426 @@ mod test {
         let mut v = IntVec::new(7);
         v.push(100000000);
     }
+
+    /// Generates pseudo-random integers fitting the given width.
+    fn random_values(width: usize, len: usize) -> Vec<usize> {
+        let mask = usize::MAX >> (usize::BITS as usize).saturating_sub(width);
+        let mut state = 0x853c_49e6_748f_ea9b_u64;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as usize & mask
+            })
+            .collect()
+    }
+
+    /// Generates pseudo-random integers fitting the given width, which may be wider than 31 bits.
+    fn wide_random_values(width: usize, len: usize) -> Vec<usize> {
+        let width = width.min(usize::BITS as usize);
+        Rng::new().values(width, len).into_iter().map(|value| value as usize).collect()
+    }
+
+    fn from_values(width: usize, values: &[usize]) -> IntVec {
+        let mut v = IntVec::new(width);
+        v.extend_from_slice(values);
+        v
+    }
+
+    #[test]
+    fn pop_test() {
+        let mut v = from_values(13, &[1, 2, 3]);
+        assert_eq!(Some(3), v.pop());
+        assert_eq!(Some(2), v.pop());
+        v.push(7);
+        assert_eq!(vec![1, 7], v.iter().collect::<Vec<_>>());
+        assert_eq!(Some(7), v.pop());
+        assert_eq!(Some(1), v.pop());
+        assert_eq!(None, v.pop());
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn insert_remove_test() {
+        for width in [1, 5, 16, 31] {
+            let mut expected = random_values(width, 200);
+            let mut v = from_values(width, &expected);
+            for (i, value) in random_values(width, 100).into_iter().enumerate() {
+                let index = (i * 37) % (expected.len() + 1);
+                v.insert(index, value);
+                expected.insert(index, value);
+                let index = (i * 53) % expected.len();
+                assert_eq!(expected.remove(index), v.remove(index));
+                let index = (i * 11) % expected.len();
+                assert_eq!(expected.swap_remove(index), v.swap_remove(index));
+            }
+            assert!(v.iter().eq(expected.iter().copied()), "width {width}");
+            assert_eq!(v.raw_data().len(), (v.len() * width) / 64 + 1);
+
+            // Bits past the end must have been cleared, so pushing still works
+            v.push(1);
+            assert_eq!(1, v.get(expected.len()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds_test() {
+        let mut v = from_values(7, &[1, 2]);
+        v.insert(3, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_too_large_number_test() {
+        let mut v = from_values(7, &[1, 2]);
+        v.insert(0, 128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds_test() {
+        let mut v = from_values(7, &[1, 2]);
+        v.remove(2);
+    }
+
+    #[test]
+    fn truncate_resize_test() {
+        let values = random_values(9, 100);
+        let mut v = from_values(9, &values);
+        v.truncate(200);
+        assert_eq!(100, v.len());
+        v.truncate(30);
+        v.resize(90, 511);
+        assert!(v.iter().eq(values[..30].iter().copied().chain([511; 60])));
+        v.resize(10, 3);
+        assert!(v.iter().eq(values[..10].iter().copied()));
+        v.resize(20, 0);
+        assert!(v.iter().eq(values[..10].iter().copied().chain([0; 10])));
+        v.clear();
+        assert!(v.is_empty());
+        assert_eq!(&[0], v.raw_data());
+        v.push(5);
+        assert_eq!(5, v.get(0));
+    }
+
+    #[test]
+    fn extend_collect_test() {
+        let mut v = IntVec::new(4);
+        v.extend([1, 2, 3]);
+        v.extend_from_slice(&[15, 0]);
+        assert_eq!(vec![1, 2, 3, 15, 0], v.iter().collect::<Vec<_>>());
+
+        let v: IntVec = [3, 1000, 7].into_iter().collect();
+        assert_eq!(10, v.width());
+        assert!(!v.is_growing());
+        assert_eq!(vec![3, 1000, 7], v.iter().collect::<Vec<_>>());
+
+        let values = random_values(20, 1000);
+        let v: IntVec = values.iter().copied().collect();
+        assert_eq!(IntVec::from_slice_min_width(&values).width(), v.width());
+        assert!(v.iter().eq(values.iter().copied()));
+
+        let v: IntVec = core::iter::empty().collect();
+        assert_eq!(1, v.width());
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn retain_dedup_test() {
+        let values = random_values(3, 300);
+        let mut v = from_values(3, &values);
+        v.retain(|x| x % 3 != 0);
+        let mut expected: Vec<_> = values.into_iter().filter(|x| x % 3 != 0).collect();
+        assert!(v.iter().eq(expected.iter().copied()));
+
+        v.dedup();
+        expected.dedup();
+        assert!(v.iter().eq(expected.iter().copied()));
+    }
+
+    #[test]
+    fn sort_test() {
+        for width in [1, 4, 20, 63] {
+            for len in [0, 1, 2, 63, 64, 65, 500] {
+                let values = random_values(width.min(33), len);
+                let mut expected = values.clone();
+                expected.sort();
+
+                let mut v = from_values(width, &values);
+                v.sort();
+                assert!(v.iter().eq(expected.iter().copied()), "width {width}, length {len}");
+
+                let mut v = from_values(width, &values);
+                v.sort_unstable();
+                assert!(v.iter().eq(expected.iter().copied()), "width {width}, length {len}");
+            }
+        }
+
+        // Partially sorted data
+        let values: Vec<_> = (0..300).chain(100..200).chain(0..50).collect();
+        let mut expected = values.clone();
+        expected.sort();
+        let mut v = from_values(9, &values);
+        v.sort();
+        assert!(v.iter().eq(expected));
+
+        // Sorting keeps growing mode, also after an odd number of merge passes
+        let mut v = from_values(4, &[9, 3, 7, 1, 5]);
+        v.set_growing(true);
+        v.sort();
+        assert!(v.is_growing());
+        v.push(1000);
+        assert_eq!(10, v.width());
+        assert_eq!(vec![1, 3, 5, 7, 9, 1000], v.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn from_slice_min_width_test() {
+        for width in [1, 2, 17, 31, 33].into_iter().filter(|&w| w < usize::BITS as usize) {
+            let mut values = wide_random_values(width, 100);
+            values.push(usize::MAX >> (usize::BITS as usize - width));
+            let v = IntVec::from_slice_min_width(&values);
+            assert_eq!(width, v.width());
+            assert!(v.iter().eq(values.iter().copied()));
+        }
+        assert_eq!(1, IntVec::from_slice_min_width(&[]).width());
+        assert_eq!(1, IntVec::from_slice_min_width(&[0, 0]).width());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(target_pointer_width = "64")]
+    fn from_slice_min_width_too_large_test() {
+        IntVec::from_slice_min_width(&[usize::MAX]);
+    }
+
+    #[test]
+    fn shrink_width_test() {
+        let values = random_values(11, 300);
+        let mut v = from_values(40, &values);
+        v.shrink_width();
+        assert_eq!(11, v.width());
+        assert!(v.iter().eq(values.iter().copied()));
+        assert_eq!(v.raw_data().len(), (300 * 11) / 64 + 1);
+        v.push(3);
+        assert_eq!(3, v.get(300));
+
+        let mut empty = IntVec::new(9);
+        empty.shrink_width();
+        assert_eq!(1, empty.width());
+    }
+
+    #[test]
+    fn growing_test() {
+        let mut v = IntVec::new(1);
+        v.set_growing(true);
+        assert!(v.is_growing());
+        let mut expected = Vec::new();
+        let width = 40.min(usize::BITS as usize);
+        for (i, value) in wide_random_values(width, 200).into_iter().enumerate() {
+            let value = value >> (width - 1).saturating_sub(i / 5);
+            v.push(value);
+            expected.push(value);
+        }
+        let max = expected.iter().max().unwrap();
+        assert_eq!((usize::BITS - max.leading_zeros()) as usize, v.width());
+        assert!(v.iter().eq(expected.iter().copied()));
+
+        let mut v = from_values(3, &[1, 2, 3]);
+        v.set_growing(true);
+        v.set(1, 8);
+        assert_eq!(4, v.width());
+        v.insert(0, 100);
+        v.resize(6, 300);
+        v.extend_from_slice(&[1000]);
+        assert_eq!(10, v.width());
+        assert_eq!(vec![100, 1, 8, 3, 300, 300, 1000], v.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn growing_disabled_test() {
+        let mut v = IntVec::new(4);
+        v.set_growing(true);
+        v.set_growing(false);
+        v.push(16);
+    }
+
+    #[test]
+    fn decode_range_test() {
+        for width in [1, 6, 16, 31, 45, 63] {
+            let values = wide_random_values(width, 500);
+            let v = from_values(width, &values);
+            for (start, end) in [(0, 500), (3, 400), (100, 101), (499, 500), (250, 250)] {
+                let mut out = vec![0u64; end - start];
+                v.decode_range(start..end, &mut out);
+                assert!(out.iter().map(|&x| x as usize).eq(values[start..end].iter().copied()));
+                if width <= 16 {
+                    let mut out = vec![0u16; end - start];
+                    v.decode_range(start..end, &mut out);
+                    assert!(out.iter().map(|&x| x as usize).eq(values[start..end].iter().copied()));
+                }
+            }
+            let mut out = vec![0u64; 500];
+            v.decode_range(.., &mut out);
+            assert!(out.iter().map(|&x| x as usize).eq(values.iter().copied()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_range_out_of_bounds_test() {
+        let v = from_values(5, &[1, 2, 3]);
+        v.decode_range(1..4, &mut [0u64; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "but length is 3")]
+    fn decode_range_overflow_test() {
+        let v = from_values(5, &[1, 2, 3]);
+        v.decode_range(..=usize::MAX, &mut [0u64; 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "but length is 3")]
+    fn decode_range_excluded_start_overflow_test() {
+        let v = from_values(5, &[1, 2, 3]);
+        let range = (Bound::Excluded(usize::MAX), Bound::Unbounded);
+        v.decode_range(range, &mut [0u64; 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_range_length_mismatch_test() {
+        let v = from_values(5, &[1, 2, 3]);
+        v.decode_range(1..3, &mut [0u64; 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_range_narrow_output_test() {
+        let v = from_values(9, &[1, 2, 3]);
+        v.decode_range(.., &mut [0u8; 3]);
+    }
+
+    #[test]
+    fn encode_from_test() {
+        for width in [1, 6, 16, 31, 45, 63] {
+            let values = wide_random_values(width, 500);
+            let words: Vec<u64> = values.iter().map(|&x| x as u64).collect();
+            for split in [0, 1, 64, 333] {
+                let mut v = from_values(width, &values[..split]);
+                v.encode_from(&words[split..]);
+                let expected = from_values(width, &values);
+                assert_eq!(expected.raw_data(), v.raw_data(), "width {width}, split {split}");
+                assert_eq!(500, v.len());
+
+                v.push(1);
+                assert_eq!(1, v.get(500));
+            }
+        }
+
+        let mut v = from_values(3, &[1, 2, 3]);
+        v.set_growing(true);
+        v.encode_from(&[4, 100]);
+        assert_eq!(7, v.width());
+        assert_eq!(vec![1, 2, 3, 4, 100], v.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_from_too_large_number_test() {
+        let mut v = IntVec::new(7);
+        v.encode_from(&[1, 128]);
+    }
+
+    #[test]
+    fn decode_chunks_test() {
+        let values = random_values(21, 1000);
+        let v = from_values(21, &values);
+        for chunk_len in [1, 7, 64, 1000, 2000] {
+            let chunks = v.decode_chunks(chunk_len);
+            assert_eq!(1000usize.div_ceil(chunk_len), chunks.len());
+            let decoded: Vec<_> = chunks.flatten().map(|x| x as usize).collect();
+            assert_eq!(values, decoded);
+
+            let mut chunks = v.decode_chunks(chunk_len);
+            let mut decoded = Vec::new();
+            while let Some(chunk) = chunks.next_chunk() {
+                assert!(chunk.len() == chunk_len || decoded.len() + chunk.len() == 1000);
+                decoded.extend(chunk.iter().map(|&x| x as usize));
+            }
+            assert_eq!(0, chunks.len());
+            assert_eq!(values, decoded);
+        }
+
+        // Both ways of decoding can be mixed
+        let mut chunks = v.decode_chunks(600);
+        assert_eq!(600, chunks.next_chunk().unwrap().len());
+        assert_eq!(1, chunks.len());
+        let rest = chunks.next().unwrap();
+        assert!(rest.iter().map(|&x| x as usize).eq(values[600..].iter().copied()));
+
+        let empty = IntVec::new(3);
+        assert_eq!(0, empty.decode_chunks(5).count());
+        assert_eq!(None, empty.decode_chunks(5).next_chunk());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn serialize_test() {
+        // Wider integers than a usize can not be read
+        for width in [1, 7, 23, 32, 63].into_iter().filter(|&w| w <= usize::BITS as usize) {
+            for len in [0usize, 1, 64, 100] {
+                let mut v = IntVec::new(width);
+                let mask = usize::MAX >> (usize::BITS as usize).saturating_sub(width);
+                for i in 0..len {
+                    v.push(i.wrapping_mul(0x9e37_79b9) & mask);
+                }
+                let mut buf = Vec::new();
+                v.write_to(&mut buf).unwrap();
+                assert_eq!(24 + 8 * (len * width).div_ceil(64), buf.len());
+
+                let mut read = IntVec::read_from(&mut buf.as_slice()).unwrap();
+                assert_eq!(v.raw_data(), read.raw_data(), "width {width}, length {len}");
+                assert_eq!(v.iter().collect::<Vec<_>>(), read.iter().collect::<Vec<_>>());
+
+                // The vector must still be usable after reading it
+                read.push(1);
+                assert_eq!(1, read.get(len));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deserialize_invalid_test() {
+        let mut v = IntVec::new(10);
+        for i in 0..10 {
+            v.push(i);
+        }
+        let mut buf = Vec::new();
+        v.write_to(&mut buf).unwrap();
+
+        let res = IntVec::read_from(&mut &buf[..buf.len() - 1]);
+        assert!(matches!(res, Err(SerializeError::Io(_))));
+
+        // A bit past the end is set
+        let mut corrupted = buf.clone();
+        *corrupted.last_mut().unwrap() = 0x80;
+        let res = IntVec::read_from(&mut corrupted.as_slice());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // Invalid width
+        let mut corrupted = buf.clone();
+        corrupted[8..16].copy_from_slice(&0u64.to_le_bytes());
+        let res = IntVec::read_from(&mut corrupted.as_slice());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // Overflowing length
+        let mut corrupted = buf.clone();
+        corrupted[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        let res = IntVec::read_from(&mut corrupted.as_slice());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+    }
 }
@@ -0,0 +1,266 @@
+//! Kernels for unpacking and packing many bit-packed integers at once.
+//!
+//! On x86-64, integers are unpacked four at a time using AVX2 gathers and variable shifts if the
+//! CPU supports it, which is detected at runtime. Without the `std` feature, AVX2 is only used if
+//! it is enabled at compile time. Otherwise, and for the integers at the very end of the data,
+//! the integers are unpacked one at a time. Packing always works a word at a time, so every word
+//! is written exactly once.
+
+use crate::bit_vec::{Word, WORD_SIZE};
+use crate::traits::BlockType;
+
+/// The mask containing the lowest `width` bits, for widths between 1 and 64.
+#[inline]
+fn mask(width: usize) -> Word {
+    Word::MAX >> (WORD_SIZE - width)
+}
+
+/// Unpacks consecutive integers of the given width into a slice.
+///
+/// # Arguments
+///
+/// * `words` - The words containing the packed integers.
+/// * `width` - The width of the integers in bits. This must be between 1 and the number of bits
+///   in `T`.
+/// * `first` - The index of the first integer to unpack.
+/// * `out` - The slice to write the integers to. Its length determines how many integers are
+///   unpacked.
+///
+/// # Panics
+///
+/// If the integers are not contained in the words.
+#[inline]
+pub(crate) fn unpack<T: BlockType>(words: &[Word], width: usize, first: usize, out: &mut [T]) {
+    debug_assert!(0 < width && width <= T::BITS.min(WORD_SIZE));
+    if (first + out.len()) * width > words.len() * WORD_SIZE {
+        panic!("integers exceed the {} packed words", words.len())
+    }
+    #[cfg(target_arch = "x86_64")]
+    let done = if x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is supported and the integers are in bounds
+        unsafe { x86::unpack_avx2(words, width, first, out) }
+    } else {
+        0
+    };
+    #[cfg(not(target_arch = "x86_64"))]
+    let done = 0;
+    // SAFETY: The integers are in bounds
+    unsafe { unpack_scalar(words, width, first + done, &mut out[done..]) }
+}
+
+/// Unpacks consecutive integers one at a time.
+///
+/// # Safety
+///
+/// The integers must be contained in the words.
+#[inline]
+unsafe fn unpack_scalar<T: BlockType>(words: &[Word], width: usize, first: usize, out: &mut [T]) {
+    let mask = mask(width);
+    let mut pos = first * width;
+    for value in out {
+        let (index, offset) = (pos / WORD_SIZE, pos % WORD_SIZE);
+        let mut word = *words.get_unchecked(index) >> offset;
+        if offset + width > WORD_SIZE {
+            word |= *words.get_unchecked(index + 1) << (WORD_SIZE - offset);
+        }
+        *value = T::from_word(word & mask);
+        pos += width;
+    }
+}
+
+/// Packs integers into consecutive positions of the given width, overwriting the bits there. Bits
+/// behind the last integer in its word are set to zero.
+///
+/// # Arguments
+///
+/// * `words` - The words to write the packed integers to.
+/// * `width` - The width of the integers in bits. This must be between 1 and 64.
+/// * `first` - The position of the first integer.
+/// * `values` - The integers to pack. They must fit into `width` bits.
+///
+/// # Panics
+///
+/// If the integers are not contained in the words.
+pub(crate) fn pack(words: &mut [Word], width: usize, first: usize, values: &[Word]) {
+    debug_assert!(0 < width && width <= WORD_SIZE);
+    debug_assert!(values.iter().all(|&value| value & !mask(width) == 0));
+    if values.is_empty() {
+        return;
+    }
+    if (first + values.len()) * width > words.len() * WORD_SIZE {
+        panic!("integers exceed the {} packed words", words.len())
+    }
+    let pos = first * width;
+    let (mut index, mut offset) = (pos / WORD_SIZE, pos % WORD_SIZE);
+    // Keep the bits in front of the first integer
+    let mut current = words[index] & !(Word::MAX << offset);
+    for &value in values {
+        current |= value << offset;
+        offset += width;
+        if offset >= WORD_SIZE {
+            words[index] = current;
+            index += 1;
+            offset -= WORD_SIZE;
+            // The bits of the value which did not fit into the previous word
+            current = if offset == 0 {
+                0
+            } else {
+                value >> (width - offset)
+            };
+        }
+    }
+    if offset > 0 {
+        words[index] = current;
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use core::arch::x86_64::*;
+
+    use super::{mask, Word, WORD_SIZE};
+    use crate::traits::BlockType;
+
+    /// Unpacks consecutive integers four at a time using AVX2. For each integer, the word
+    /// containing its first bit and the word after it are gathered and shifted into place.
+    /// Integers are unpacked as long as the word after the last one of the group is in bounds.
+    ///
+    /// returns: The number of integers which were unpacked. These are always a prefix of `out`.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2 and the integers must be contained in the words.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn unpack_avx2<T: BlockType>(
+        words: &[Word],
+        width: usize,
+        first: usize,
+        out: &mut [T],
+    ) -> usize {
+        let ptr = words.as_ptr() as *const i64;
+        let step = _mm256_set1_epi64x(4 * width as i64);
+        let word_mask = _mm256_set1_epi64x((WORD_SIZE - 1) as i64);
+        let value_mask = _mm256_set1_epi64x(mask(width) as i64);
+        let word_size = _mm256_set1_epi64x(WORD_SIZE as i64);
+        let one = _mm256_set1_epi64x(1);
+        let w = width as i64;
+        let start = (first * width) as i64;
+        let mut positions = _mm256_setr_epi64x(start, start + w, start + 2 * w, start + 3 * w);
+
+        let mut lanes = [0u64; 4];
+        let mut done = 0;
+        // The word after the one containing the first bit of the last integer must exist
+        while done + 4 <= out.len() && ((first + done + 3) * width) / WORD_SIZE + 1 < words.len() {
+            let indices = _mm256_srli_epi64::<6>(positions);
+            let offsets = _mm256_and_si256(positions, word_mask);
+            let low = _mm256_i64gather_epi64::<8>(ptr, indices);
+            let high = _mm256_i64gather_epi64::<8>(ptr, _mm256_add_epi64(indices, one));
+            // Shifting by 64 bits results in 0, so integers inside of a single word work as well
+            let values = _mm256_or_si256(
+                _mm256_srlv_epi64(low, offsets),
+                _mm256_sllv_epi64(high, _mm256_sub_epi64(word_size, offsets)),
+            );
+            let values = _mm256_and_si256(values, value_mask);
+            _mm256_storeu_si256(lanes.as_mut_ptr().cast(), values);
+            for (value, &lane) in out[done..done + 4].iter_mut().zip(&lanes) {
+                *value = T::from_word(lane);
+            }
+            positions = _mm256_add_epi64(positions, step);
+            done += 4;
+        }
+        done
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{pack, unpack, unpack_scalar, Word};
+    use crate::test_util::Rng;
+
+    /// Packs the integers one at a time as a reference.
+    fn pack_naive(width: usize, values: &[Word]) -> Vec<Word> {
+        let mut words = vec![0; (values.len() * width).div_ceil(64) + 1];
+        for (i, &value) in values.iter().enumerate() {
+            for bit in 0..width {
+                let pos = i * width + bit;
+                words[pos / 64] |= ((value >> bit) & 1) << (pos % 64);
+            }
+        }
+        words
+    }
+
+    #[test]
+    fn unpack_test() {
+        for width in [1, 3, 8, 13, 32, 45, 63, 64] {
+            let values = Rng::new().values(width, 300);
+            let words = pack_naive(width, &values);
+            for (first, len) in [(0, 300), (1, 299), (7, 50), (100, 3), (297, 3), (5, 0)] {
+                let mut out = vec![0u64; len];
+                unpack(&words, width, first, &mut out);
+                assert_eq!(
+                    &values[first..first + len],
+                    &out,
+                    "width {width}, first {first}"
+                );
+
+                let mut scalar = vec![0u64; len];
+                unsafe { unpack_scalar(&words, width, first, &mut scalar) };
+                assert_eq!(out, scalar);
+
+                if width <= 32 {
+                    let mut narrow = vec![0u32; len];
+                    unpack(&words, width, first, &mut narrow);
+                    assert!(narrow.iter().map(|&v| v as Word).eq(out.iter().copied()));
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn unpack_avx2_test() {
+        if !x86_feature_detected!("avx2") {
+            return;
+        }
+        for width in [1, 7, 31, 64] {
+            let values = Rng::new().values(width, 100);
+            // Without the trailing word, the last integers must be left to the scalar kernel
+            let words = &pack_naive(width, &values)[..(100 * width).div_ceil(64)];
+            for first in 0..8 {
+                let mut out = vec![0u64; 100 - first];
+                let done = unsafe { super::x86::unpack_avx2(words, width, first, &mut out) };
+                assert!(done > 0 && done % 4 == 0, "width {width}");
+                assert_eq!(&values[first..first + done], &out[..done], "width {width}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn unpack_out_of_bounds_test() {
+        let words = [0; 2];
+        let mut out = [0u64; 11];
+        unpack(&words, 12, 0, &mut out);
+    }
+
+    #[test]
+    fn pack_test() {
+        for width in [1, 3, 8, 13, 32, 45, 63, 64] {
+            let values = Rng::new().values(width, 300);
+            let expected = pack_naive(width, &values);
+            for split in [0, 1, 64, 150, 299, 300] {
+                // Existing bits behind the packed integers must be cleared
+                let mut words = vec![Word::MAX; expected.len()];
+                pack(&mut words, width, 0, &values[..split]);
+                pack(&mut words, width, split, &values[split..]);
+                let bits = 300 * width;
+                words[bits / 64] &= !(Word::MAX << (bits % 64));
+                words[bits / 64 + 1..].fill(0);
+                assert_eq!(expected, words, "width {width}, split {split}");
+            }
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use super::{IntAccess, IntVec, Iter, SignedIntAccess};
+
+/// The largest supported width. The zig-zag encoded integers must fit into a `usize`, and an
+/// [`IntVec`] stores at most 63 bits per integer.
+const MAX_WIDTH: usize = if usize::BITS < 64 { usize::BITS as usize } else { 63 };
+
+/// The smallest integer fitting the largest supported width.
+const MIN: i64 = -1 << (MAX_WIDTH - 1);
+
+/// The largest integer fitting the largest supported width.
+const MAX: i64 = (1 << (MAX_WIDTH - 1)) - 1;
+
+/// Maps a signed integer to an unsigned one using zig-zag encoding, such that integers with a small
+/// absolute value are mapped to small integers: `0, -1, 1, -2, 2, ...` become `0, 1, 2, 3, 4, ...`.
+#[inline]
+const fn zig_zag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Reverses [`zig_zag`].
+#[inline]
+const fn unzig_zag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// A vector of bit-packed signed integers with a fixed width.
+///
+/// The integers are stored in an [`IntVec`] using zig-zag encoding, so a width of `w` bits
+/// permits all integers from $-2^{w-1}$ to $2^{w-1} - 1$, just like a `w` bit two's complement
+/// integer would. The width must be between 1 and 63, or the number of bits in a `usize` if that
+/// is smaller, so the integers are limited to $-2^{62}$ to $2^{62} - 1$ on 64-bit targets.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::int_vec::{SignedIntAccess, SignedIntVec};
+///
+/// let mut v = SignedIntVec::new(5);
+/// v.push(-16);
+/// v.push(15);
+/// v.set(1, -3);
+///
+/// assert_eq!(-16, v.get(0));
+/// assert_eq!(-3, v.get(1));
+/// assert_eq!((-16, 15), (v.min_value(), v.max_value()));
+/// ```
+#[derive(Debug)]
+pub struct SignedIntVec {
+    data: IntVec,
+}
+
+impl SignedIntVec {
+    /// Creates an empty vector storing integers with the given width in bits, including the sign.
+    ///
+    /// # Panics
+    ///
+    /// If the width is 0 or larger than 63 bits, or the number of bits in a `usize` if that is
+    /// smaller.
+    #[inline]
+    pub fn new(width: usize) -> Self {
+        Self::with_capacity(width, 8)
+    }
+
+    /// Creates an empty vector storing integers with the given width in bits, including the sign,
+    /// with space for about `capacity` integers.
+    ///
+    /// # Panics
+    ///
+    /// If the width is 0 or larger than 63 bits, or the number of bits in a `usize` if that is
+    /// smaller.
+    #[inline]
+    pub fn with_capacity(width: usize, capacity: usize) -> Self {
+        if width == 0 || width > MAX_WIDTH {
+            panic!("width must be between 1 and {MAX_WIDTH} but is {width}")
+        }
+        Self {
+            data: IntVec::with_capacity(width, capacity),
+        }
+    }
+
+    /// Creates a vector containing the given integers, using the smallest width that fits all of
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// If an integer does not fit the largest supported width, i.e. it is outside of
+    /// $-2^{62}$ to $2^{62} - 1$ on 64-bit targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::int_vec::{SignedIntAccess, SignedIntVec};
+    ///
+    /// let v = SignedIntVec::from_slice_min_width(&[3, -100, 7]);
+    ///
+    /// assert_eq!(8, v.width());
+    /// assert_eq!(-100, v.get(1));
+    /// ```
+    pub fn from_slice_min_width(values: &[i64]) -> Self {
+        let width = values
+            .iter()
+            .map(|&value| Self::min_width(value))
+            .max()
+            .unwrap_or(1);
+        let mut v = Self::with_capacity(width, values.len());
+        v.extend(values.iter().copied());
+        v
+    }
+
+    /// Gets the smallest width which fits the given value, including the sign.
+    ///
+    /// # Panics
+    ///
+    /// If the value does not fit the largest supported width.
+    #[inline]
+    fn min_width(value: i64) -> usize {
+        if !(MIN..=MAX).contains(&value) {
+            panic!("value {value} out of range {MIN}..={MAX} for a signed integer vector")
+        }
+        // Besides the sign, a negative value needs as many bits as its complement
+        (i64::BITS - (value ^ (value >> 63)).leading_zeros()) as usize + 1
+    }
+
+    /// Gets the number of bits used to store each integer, including the sign.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.data.width()
+    }
+
+    /// Gets the smallest integer which fits the width of this vector.
+    #[inline]
+    pub fn min_value(&self) -> i64 {
+        -1 << (self.width() - 1)
+    }
+
+    /// Gets the largest integer which fits the width of this vector.
+    #[inline]
+    pub fn max_value(&self) -> i64 {
+        (1 << (self.width() - 1)) - 1
+    }
+
+    /// Gets the number of integers stored in this vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Gets the underlying vector containing the zig-zag encoded integers.
+    #[inline]
+    pub fn encoded(&self) -> &IntVec {
+        &self.data
+    }
+
+    #[inline]
+    pub fn iter(&self) -> SignedIter<'_> {
+        SignedIter {
+            inner: self.data.iter(),
+        }
+    }
+
+    /// Appends an integer to the back of the vector.
+    ///
+    /// # Panics
+    ///
+    /// If the value is outside of the range permitted by the width.
+    pub fn push(&mut self, value: i64) {
+        self.data.push(self.encode(value));
+    }
+
+    /// Removes the last integer and returns it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<i64> {
+        self.data.pop().map(|value| unzig_zag(value as u64))
+    }
+
+    /// Shortens the vector to the given length. This has no effect if the vector is already
+    /// shorter.
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+    }
+
+    /// Removes all integers from the vector.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Zig-zag encodes a value while checking that it fits the width of this vector.
+    #[inline]
+    fn encode(&self, value: i64) -> usize {
+        if value < self.min_value() || value > self.max_value() {
+            panic!(
+                "value {value} out of range {}..={} for {}-bit signed integer",
+                self.min_value(),
+                self.max_value(),
+                self.width()
+            )
+        }
+        zig_zag(value) as usize
+    }
+}
+
+impl SignedIntAccess for SignedIntVec {
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> i64 {
+        unzig_zag(self.data.get_unchecked(index) as u64)
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> i64 {
+        unzig_zag(self.data.get(index) as u64)
+    }
+
+    #[inline]
+    unsafe fn set_unchecked(&mut self, index: usize, value: i64) {
+        self.data.set_unchecked(index, zig_zag(value) as usize)
+    }
+
+    #[inline]
+    fn set(&mut self, index: usize, value: i64) {
+        let value = self.encode(value);
+        self.data.set(index, value)
+    }
+}
+
+impl Extend<i64> for SignedIntVec {
+    fn extend<T: IntoIterator<Item = i64>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.data.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Collects integers into a vector using the smallest width that fits all of them.
+///
+/// # Panics
+///
+/// If an integer does not fit the largest supported width, i.e. it is outside of $-2^{62}$ to
+/// $2^{62} - 1$ on 64-bit targets.
+impl FromIterator<i64> for SignedIntVec {
+    fn from_iter<T: IntoIterator<Item = i64>>(iter: T) -> Self {
+        // Zig-zag encoding a w bit signed integer results in a w bit unsigned integer, so the
+        // smallest width of the encoded integers is the smallest width of the signed ones
+        let data: IntVec = iter
+            .into_iter()
+            .map(|value| {
+                // Panics if the value does not fit the largest supported width
+                Self::min_width(value);
+                zig_zag(value) as usize
+            })
+            .collect();
+        Self { data }
+    }
+}
+
+impl<'a> IntoIterator for &'a SignedIntVec {
+    type Item = i64;
+
+    type IntoIter = SignedIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the integers in a [`SignedIntVec`].
+pub struct SignedIter<'a> {
+    inner: Iter<'a>,
+}
+
+impl Iterator for SignedIter<'_> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|value| unzig_zag(value as u64))
+    }
+}
+
+impl ExactSizeIterator for SignedIter<'_> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use super::{unzig_zag, zig_zag, SignedIntAccess, SignedIntVec, MAX, MAX_WIDTH, MIN};
+
+    #[test]
+    fn zig_zag_test() {
+        assert_eq!([0, 1, 2, 3, 4], [0, -1, 1, -2, 2].map(zig_zag));
+        assert_eq!(u64::MAX, zig_zag(i64::MIN));
+        assert_eq!(u64::MAX - 1, zig_zag(i64::MAX));
+        for value in [0, 1, -1, 1000, -1000, i64::MIN, i64::MAX] {
+            assert_eq!(value, unzig_zag(zig_zag(value)));
+        }
+    }
+
+    #[test]
+    fn push_get_set_test() {
+        for width in [1, 2, 7, 32, 63].into_iter().filter(|&w| w <= MAX_WIDTH) {
+            let mut v = SignedIntVec::new(width);
+            let (min, max) = (v.min_value(), v.max_value());
+            assert_eq!(-(1 << (width - 1)), min);
+            assert_eq!((1 << (width - 1)) - 1, max);
+
+            let values: Vec<i64> = [min, max, 0, -1, min / 3, max / 3]
+                .into_iter()
+                .cycle()
+                .take(100)
+                .collect();
+            v.extend(values.iter().copied());
+            assert!(v.iter().eq(values.iter().copied()), "width {width}");
+            assert_eq!(100, v.iter().len());
+
+            for i in 0..100 {
+                v.set(i, values[99 - i]);
+            }
+            assert!((0..100).all(|i| v.get(i) == values[99 - i]));
+            assert_eq!(Some(values[0]), v.pop());
+        }
+    }
+
+    #[test]
+    fn from_slice_min_width_test() {
+        assert_eq!(1, SignedIntVec::from_slice_min_width(&[0, -1]).width());
+        assert_eq!(2, SignedIntVec::from_slice_min_width(&[1, -2]).width());
+        assert_eq!(8, SignedIntVec::from_slice_min_width(&[127, -128]).width());
+        assert_eq!(9, SignedIntVec::from_slice_min_width(&[128]).width());
+
+        assert_eq!(MAX_WIDTH, SignedIntVec::from_slice_min_width(&[MIN, MAX]).width());
+
+        let v: SignedIntVec = [-5, 3, 0, -70000].into_iter().collect();
+        assert_eq!(18, v.width());
+        assert_eq!(vec![-5, 3, 0, -70000], v.iter().collect::<Vec<_>>());
+
+        let v: SignedIntVec = [MAX, 0, MIN].into_iter().collect();
+        assert_eq!(MAX_WIDTH, v.width());
+        assert_eq!(vec![MAX, 0, MIN], v.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn from_slice_min_width_too_large_test() {
+        SignedIntVec::from_slice_min_width(&[0, MAX + 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn collect_too_small_test() {
+        let _: SignedIntVec = [0, MIN - 1].into_iter().collect();
+    }
+
+    #[test]
+    #[should_panic(expected = "width must be between")]
+    fn new_too_wide_test() {
+        SignedIntVec::new(64);
+    }
+
+    #[test]
+    #[should_panic(expected = "width must be between")]
+    fn new_zero_width_test() {
+        SignedIntVec::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range -8..=7")]
+    fn push_too_large_number_test() {
+        let mut v = SignedIntVec::new(4);
+        v.push(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range -8..=7")]
+    fn set_too_small_number_test() {
+        let mut v = SignedIntVec::new(4);
+        v.push(0);
+        v.set(0, -9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds_test() {
+        let v = SignedIntVec::new(4);
+        v.get(0);
+    }
+}
@@ -25,3 +25,30 @@ pub trait IntAccess {
     fn set(&mut self, index: usize, value: usize);
 }
 
+
+/// Allows access to signed integers in a datastructure
+pub trait SignedIntAccess {
+    /// Gets a signed integer without making any checks for bounds etc.
+    ///
+    /// # Safety
+    ///
+    /// In general, this expects `index` to be in bounds of the datastructure.
+    /// However, other type-specific contracts might exist.
+    unsafe fn get_unchecked(&self, index: usize) -> i64;
+
+    /// Gets the signed integer at an index while checking for bounds.
+    fn get(&self, index: usize) -> i64;
+
+    /// Sets a signed integer to the given value without making any checks for bounds etc.
+    ///
+    /// # Safety
+    ///
+    /// In general, this expects `index` to be in bounds of the datastructure and
+    /// the value to be in the range permitted by the width of the data structure.
+    /// However, other type-specific contracts might exists.
+    unsafe fn set_unchecked(&mut self, index: usize, value: i64);
+
+    /// Sets the signed integer at an index to the given value while checking for bounds and
+    /// whether the value is in the permitted range.
+    fn set(&mut self, index: usize, value: i64);
+}
@@ -1,8 +1,35 @@
 #![deny(rustdoc::broken_intra_doc_links)] // error if there are broken intra-doc links
 #![deny(rustdoc::invalid_html_tags)] // no broken html in docs
 #![deny(rustdoc::invalid_rust_codeblocks)] // code blocks should not be broken
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+/// Checks whether the CPU supports all of the given x86 target features.
+///
+/// With the `std` feature, this is detected at runtime. Otherwise, only features enabled at
+/// compile time (e.g. using `-C target-feature`) are reported as supported.
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+macro_rules! x86_feature_detected {
+    ($($feature:tt),+) => {
+        $(std::is_x86_feature_detected!($feature))&&+
+    };
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+macro_rules! x86_feature_detected {
+    ($($feature:tt),+) => {
+        cfg!(all($(target_feature = $feature),+))
+    };
+}
 
 pub mod rank_select;
 pub mod bit_vec;
+pub mod elias_fano;
 pub mod int_vec;
+mod popcount;
+pub mod serialize;
+#[cfg(test)]
+pub(crate) mod test_util;
 pub mod traits;
+pub mod wavelet_matrix;
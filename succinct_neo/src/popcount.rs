@@ -0,0 +1,399 @@
+//! Popcount kernels for counting the ones in many words at once.
+//!
+//! On x86-64, the best kernel supported by the CPU is selected at runtime: `VPOPCNTQ` if AVX-512
+//! is available, a Harley–Seal carry-save adder network on AVX2, or the scalar `POPCNT`
+//! instruction. This way, portable binaries still make use of these instructions. Without the `std`
+//! feature, only the instructions enabled at compile time are used. On all other targets, and for
+//! inputs too short to benefit, the counts are computed using [`u64::count_ones`].
+//!
+//! Blocks of [`BLOCK_WORDS`] words, which are exactly the L1 blocks of the rank/select data
+//! structures, can be counted group-wise using a [`BlockKernel`]. Since the kernel is selected
+//! once by [`block_kernel`], counting many blocks does not repeat the feature detection.
+
+use crate::bit_vec::Word;
+
+/// The number of words buffered by [`count_ones_iter`] before counting them.
+const CHUNK_WORDS: usize = 64;
+
+/// The number of words in a block counted by a [`BlockKernel`].
+pub(crate) const BLOCK_WORDS: usize = 64;
+
+/// The number of words in each group of a block counted by a [`BlockKernel`].
+pub(crate) const GROUP_WORDS: usize = 8;
+
+/// A kernel counting the ones in each group of [`GROUP_WORDS`] consecutive words in a block of
+/// [`BLOCK_WORDS`] words.
+///
+/// # Safety
+///
+/// The kernel may use instructions the CPU does not support. Kernels returned by [`block_kernel`]
+/// are always safe to call.
+pub(crate) type BlockKernel = unsafe fn(&[Word; BLOCK_WORDS]) -> [usize; BLOCK_WORDS / GROUP_WORDS];
+
+/// Counts the ones in a slice of words.
+#[inline]
+pub(crate) fn count_ones(words: &[Word]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: The required target features are checked right before
+        if words.len() >= 8 && x86::avx512_detected() {
+            return unsafe { x86::count_ones_avx512(words) };
+        }
+        if words.len() >= 4 && x86_feature_detected!("avx2") {
+            return unsafe { x86::count_ones_avx2(words) };
+        }
+        if x86_feature_detected!("popcnt") {
+            return unsafe { x86::count_ones_popcnt(words) };
+        }
+    }
+    count_ones_scalar(words)
+}
+
+/// Counts the ones in all words produced by an iterator.
+///
+/// The words are collected into chunks, which are then counted using [`count_ones`]. This allows
+/// counting words which are not stored contiguously, e.g. those of an unaligned bit slice.
+#[inline]
+pub(crate) fn count_ones_iter(words: impl IntoIterator<Item = Word>) -> usize {
+    count_ones_chunked::<CHUNK_WORDS>(words)
+}
+
+/// Counts the ones in a few words produced by an iterator, e.g. those inside of a single L2 block.
+///
+/// These are too few for the vectorized kernels to pay off, so only the `POPCNT` instruction is
+/// selected at runtime.
+#[inline]
+pub(crate) fn count_ones_few(words: impl IntoIterator<Item = Word>) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if x86_feature_detected!("popcnt") {
+            // SAFETY: We checked that the CPU supports POPCNT
+            return unsafe { x86::count_ones_few_popcnt(words) };
+        }
+    }
+    words.into_iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// Selects the best [`BlockKernel`] supported by the CPU. The returned kernel only uses
+/// instructions the CPU supports, so it is always safe to call.
+pub(crate) fn block_kernel() -> BlockKernel {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if x86::avx512_detected() {
+            return x86::count_groups_avx512;
+        }
+        if x86_feature_detected!("avx2") {
+            return x86::count_groups_avx2;
+        }
+        if x86_feature_detected!("popcnt") {
+            return x86::count_groups_popcnt;
+        }
+    }
+    count_groups_scalar
+}
+
+#[inline]
+fn count_ones_chunked<const CHUNK: usize>(words: impl IntoIterator<Item = Word>) -> usize {
+    let mut buf = [0; CHUNK];
+    let mut len = 0;
+    let mut total = 0;
+    for word in words {
+        buf[len] = word;
+        len += 1;
+        if len == CHUNK {
+            total += count_ones(&buf);
+            len = 0;
+        }
+    }
+    total + count_ones(&buf[..len])
+}
+
+/// Counts the ones in a slice of words one word at a time.
+#[inline]
+fn count_ones_scalar(words: &[Word]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// Counts the ones in each group of a block one word at a time.
+#[inline]
+fn count_groups_scalar(block: &[Word; BLOCK_WORDS]) -> [usize; BLOCK_WORDS / GROUP_WORDS] {
+    core::array::from_fn(|i| count_ones_scalar(&block[i * GROUP_WORDS..(i + 1) * GROUP_WORDS]))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use core::arch::x86_64::*;
+
+    use super::{Word, BLOCK_WORDS, GROUP_WORDS};
+
+    /// The number of groups in a block.
+    const GROUPS: usize = BLOCK_WORDS / GROUP_WORDS;
+
+    /// Returns `true` if the CPU supports the AVX-512 popcount instructions.
+    #[inline]
+    pub(super) fn avx512_detected() -> bool {
+        x86_feature_detected!("avx512f", "avx512vpopcntdq")
+    }
+
+    /// Counts the ones in a slice of words using the `POPCNT` instruction.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support `POPCNT`.
+    #[target_feature(enable = "popcnt")]
+    pub(super) unsafe fn count_ones_popcnt(words: &[Word]) -> usize {
+        super::count_ones_scalar(words)
+    }
+
+    /// Counts the ones in a slice of words using `VPOPCNTQ`, eight words at a time.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX-512F and AVX-512 VPOPCNTDQ.
+    #[target_feature(enable = "avx512f,avx512vpopcntdq")]
+    pub(super) unsafe fn count_ones_avx512(words: &[Word]) -> usize {
+        let chunks = words.chunks_exact(8);
+        let rest = chunks.remainder();
+        let mut acc = _mm512_setzero_si512();
+        for chunk in chunks {
+            let v = _mm512_loadu_si512(chunk.as_ptr().cast());
+            acc = _mm512_add_epi64(acc, _mm512_popcnt_epi64(v));
+        }
+        // Load the remaining words while masking out the lanes past the end of the slice
+        let mask = ((1u16 << rest.len()) - 1) as __mmask8;
+        let v = _mm512_maskz_loadu_epi64(mask, rest.as_ptr().cast());
+        acc = _mm512_add_epi64(acc, _mm512_popcnt_epi64(v));
+        _mm512_reduce_add_epi64(acc) as usize
+    }
+
+    /// Counts the ones in the words produced by an iterator using the `POPCNT` instruction.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support `POPCNT`.
+    #[inline]
+    #[target_feature(enable = "popcnt")]
+    pub(super) unsafe fn count_ones_few_popcnt(words: impl IntoIterator<Item = Word>) -> usize {
+        words.into_iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Counts the ones in each group of a block using the `POPCNT` instruction.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support `POPCNT`.
+    #[target_feature(enable = "popcnt")]
+    pub(super) unsafe fn count_groups_popcnt(block: &[Word; BLOCK_WORDS]) -> [usize; GROUPS] {
+        super::count_groups_scalar(block)
+    }
+
+    /// Counts the ones in each group of a block using `VPOPCNTQ`, one vector per group.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX-512F and AVX-512 VPOPCNTDQ.
+    #[target_feature(enable = "avx512f,avx512vpopcntdq")]
+    pub(super) unsafe fn count_groups_avx512(block: &[Word; BLOCK_WORDS]) -> [usize; GROUPS] {
+        core::array::from_fn(|i| {
+            let v = _mm512_loadu_si512(block.as_ptr().add(i * GROUP_WORDS).cast());
+            _mm512_reduce_add_epi64(_mm512_popcnt_epi64(v)) as usize
+        })
+    }
+
+    /// Counts the ones in each group of a block using AVX2, two vectors per group. The lanes of
+    /// four groups at a time are added up by transposing them, which avoids a horizontal sum per
+    /// group.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_groups_avx2(block: &[Word; BLOCK_WORDS]) -> [usize; GROUPS] {
+        let load = |i: usize| _mm256_loadu_si256(block.as_ptr().add(4 * i).cast());
+        // The lane counts of a group
+        let group = |i: usize| {
+            _mm256_add_epi64(popcount_256(load(2 * i)), popcount_256(load(2 * i + 1)))
+        };
+        let mut counts = [0u64; GROUPS];
+        for (first, out) in (0..GROUPS).step_by(4).zip(counts.chunks_exact_mut(4)) {
+            let [a, b, c, d]: [__m256i; 4] = core::array::from_fn(|i| group(first + i));
+            // Pairwise sums of the lanes of (a, b) and (c, d) in the order a, b, a, b
+            let ab = _mm256_add_epi64(_mm256_unpacklo_epi64(a, b), _mm256_unpackhi_epi64(a, b));
+            let cd = _mm256_add_epi64(_mm256_unpacklo_epi64(c, d), _mm256_unpackhi_epi64(c, d));
+            let sums = _mm256_add_epi64(
+                _mm256_permute2x128_si256::<0x20>(ab, cd),
+                _mm256_permute2x128_si256::<0x31>(ab, cd),
+            );
+            _mm256_storeu_si256(out.as_mut_ptr().cast(), sums);
+        }
+        counts.map(|count| count as usize)
+    }
+
+    /// Counts the ones in each 64 bit lane of a vector using a nibble lookup table.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn popcount_256(v: __m256i) -> __m256i {
+        #[rustfmt::skip]
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        );
+        let low_mask = _mm256_set1_epi8(0x0f);
+        let lo = _mm256_and_si256(v, low_mask);
+        let hi = _mm256_and_si256(_mm256_srli_epi16::<4>(v), low_mask);
+        let counts = _mm256_add_epi8(
+            _mm256_shuffle_epi8(lookup, lo),
+            _mm256_shuffle_epi8(lookup, hi),
+        );
+        // Sum the byte counts of each lane
+        _mm256_sad_epu8(counts, _mm256_setzero_si256())
+    }
+
+    /// A carry-save adder. Returns the carry and sum bits of adding three vectors bitwise.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    fn csa(a: __m256i, b: __m256i, c: __m256i) -> (__m256i, __m256i) {
+        let u = _mm256_xor_si256(a, b);
+        let high = _mm256_or_si256(_mm256_and_si256(a, b), _mm256_and_si256(u, c));
+        (high, _mm256_xor_si256(u, c))
+    }
+
+    /// Counts the ones in a slice of words using the Harley–Seal algorithm on AVX2 as described by
+    /// Muła, Kurz and Lemire in *Faster Population Counts Using AVX2 Instructions*.
+    ///
+    /// Blocks of 16 vectors are added up using a network of carry-save adders, so only one
+    /// vector per block has to be counted using the comparatively expensive lookup table.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2,popcnt")]
+    pub(super) unsafe fn count_ones_avx2(words: &[Word]) -> usize {
+        let load = |i: usize| _mm256_loadu_si256(words.as_ptr().add(4 * i).cast());
+        let vectors = words.len() / 4;
+        let zero = _mm256_setzero_si256();
+        let (mut total, mut ones, mut twos, mut fours, mut eights) = (zero, zero, zero, zero, zero);
+
+        let mut i = 0;
+        while i + 16 <= vectors {
+            let (twos_a, o) = csa(ones, load(i), load(i + 1));
+            let (twos_b, o) = csa(o, load(i + 2), load(i + 3));
+            let (fours_a, t) = csa(twos, twos_a, twos_b);
+            let (twos_a, o) = csa(o, load(i + 4), load(i + 5));
+            let (twos_b, o) = csa(o, load(i + 6), load(i + 7));
+            let (fours_b, t) = csa(t, twos_a, twos_b);
+            let (eights_a, f) = csa(fours, fours_a, fours_b);
+            let (twos_a, o) = csa(o, load(i + 8), load(i + 9));
+            let (twos_b, o) = csa(o, load(i + 10), load(i + 11));
+            let (fours_a, t) = csa(t, twos_a, twos_b);
+            let (twos_a, o) = csa(o, load(i + 12), load(i + 13));
+            let (twos_b, o) = csa(o, load(i + 14), load(i + 15));
+            let (fours_b, t) = csa(t, twos_a, twos_b);
+            let (eights_b, f) = csa(f, fours_a, fours_b);
+            let (sixteens, e) = csa(eights, eights_a, eights_b);
+            (ones, twos, fours, eights) = (o, t, f, e);
+
+            total = _mm256_add_epi64(total, popcount_256(sixteens));
+            i += 16;
+        }
+
+        total = _mm256_slli_epi64::<4>(total);
+        total = _mm256_add_epi64(total, _mm256_slli_epi64::<3>(popcount_256(eights)));
+        total = _mm256_add_epi64(total, _mm256_slli_epi64::<2>(popcount_256(fours)));
+        total = _mm256_add_epi64(total, _mm256_slli_epi64::<1>(popcount_256(twos)));
+        total = _mm256_add_epi64(total, popcount_256(ones));
+        while i < vectors {
+            total = _mm256_add_epi64(total, popcount_256(load(i)));
+            i += 1;
+        }
+
+        let mut lanes = [0u64; 4];
+        _mm256_storeu_si256(lanes.as_mut_ptr().cast(), total);
+        lanes.iter().sum::<u64>() as usize + super::count_ones_scalar(&words[4 * vectors..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        block_kernel, count_groups_scalar, count_ones, count_ones_few, count_ones_iter,
+        count_ones_scalar, Word, BLOCK_WORDS,
+    };
+    use crate::test_util::Rng;
+
+    fn words(len: usize) -> Vec<Word> {
+        let mut rng = Rng::new();
+        (0..len)
+            .map(|i| {
+                // Include some words which are all ones or all zeroes
+                match i % 11 {
+                    3 => Word::MAX,
+                    7 => 0,
+                    _ => rng.next_word(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn count_ones_test() {
+        let words = words(1000);
+        for len in (0..300).chain([511, 512, 513, 1000]) {
+            let expected = count_ones_scalar(&words[..len]);
+            assert_eq!(expected, count_ones(&words[..len]), "length {len}");
+            assert_eq!(expected, count_ones_iter(words[..len].iter().copied()), "length {len}");
+            assert_eq!(expected, count_ones_few(words[..len].iter().copied()), "length {len}");
+        }
+    }
+
+    #[test]
+    fn block_kernel_test() {
+        let words = words(1000);
+        let kernel = block_kernel();
+        for start in [0, 1, 64, 500, 936] {
+            let block: &[Word; BLOCK_WORDS] = words[start..start + BLOCK_WORDS].try_into().unwrap();
+            let expected: Vec<usize> = block.chunks(8).map(count_ones_scalar).collect();
+            assert_eq!(expected, count_groups_scalar(block), "start {start}");
+            assert_eq!(expected, unsafe { kernel(block) }, "start {start}");
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn kernels_test() {
+        use super::x86;
+
+        let words = words(1000);
+        // Also test unaligned starts
+        for start in 0..4 {
+            for len in (0..300).chain([511, 512, 513, 996]) {
+                let words = &words[start..start + len];
+                let expected = count_ones_scalar(words);
+                if x86_feature_detected!("popcnt") {
+                    assert_eq!(expected, unsafe { x86::count_ones_popcnt(words) });
+                }
+                if x86_feature_detected!("avx2") {
+                    assert_eq!(expected, unsafe { x86::count_ones_avx2(words) }, "avx2 {len}");
+                }
+                if x86::avx512_detected() {
+                    assert_eq!(expected, unsafe { x86::count_ones_avx512(words) }, "avx512 {len}");
+                }
+            }
+        }
+
+        for start in [0, 3, 100, 936] {
+            let block = words[start..start + BLOCK_WORDS].try_into().unwrap();
+            let expected = count_groups_scalar(block);
+            if x86_feature_detected!("popcnt") {
+                assert_eq!(expected, unsafe { x86::count_groups_popcnt(block) });
+            }
+            if x86_feature_detected!("avx2") {
+                assert_eq!(expected, unsafe { x86::count_groups_avx2(block) }, "avx2 {start}");
+            }
+            if x86::avx512_detected() {
+                let counts = unsafe { x86::count_groups_avx512(block) };
+                assert_eq!(expected, counts, "avx512 {start}");
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
-use std::marker::PhantomData;
-use crate::bit_vec::{BitGet, BitVec};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::bit_vec::slice::{BitPositions, ExactBitPositions};
+use crate::bit_vec::{BitModify, BitSlice, Word, WordAccess, WORD_EXP, WORD_MASK};
 
 use super::traits::RankSupport;
 
@@ -14,14 +19,16 @@ const L2_BLOCK_SIZE_EXP: usize = 9;
 /// The mask covering the size of an L2 index entry (12 bits)
 const L2_INDEX_MASK: u128 = (1 << 12) - 1;
 
-// This requires this computer's word size to be 64 bits
-static_assertions::assert_eq_size!(usize, u64);
-
 mod strats;
 
 pub use strats::*;
 use crate::int_vec::{IntAccess, IntVec};
+use crate::popcount;
 use crate::rank_select::traits::SelectSupport;
+use crate::serialize::{self, ReadBytes, SerializeError};
+
+/// The magic number identifying a serialized [`FlatPopcount`] index
+const FLAT_POPCOUNT_MAGIC: [u8; 4] = *b"SNFP";
 
 /// An implementation of the rank/select data structure described by Florian Kurpicz in his paper
 /// *Engineering Compact Data Structures for Rank and Select Queries on Bit Vectors*.
@@ -29,20 +36,75 @@ use crate::rank_select::traits::SelectSupport;
 ///
 /// This data structure should work well in most cases with a low memory overhead over the
 /// bitvector (less than 4%). 
-pub struct FlatPopcount<'a, Strat = LinearSearch> {
-    backing: &'a BitVec,
-    l1_index: Vec<u128>,
+///
+/// The bits can be stored in any type supporting [`WordAccess`]. This includes bit vectors, which
+/// can be borrowed or owned by this data structure (e.g. `BitVec`, `&BitVec`, `Rc<BitVec>` or
+/// `Arc<BitVec>`), bit slices (even if they do not start at a word boundary) and plain word
+/// buffers like `&[u64]` or `Vec<u64>`.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use succinct_neo::{
+///     bit_vec::BitVec,
+///     rank_select::{FlatPopcount, RankSupport}
+/// };
+///
+/// let mut bv = BitVec::new(64);
+/// bv.flip(10);
+///
+/// // Borrowing the bit vector
+/// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
+/// assert_eq!(1, rank_ds.rank::<true>(20));
+///
+/// // Sharing the bit vector
+/// let shared = Rc::new(bv);
+/// let rank_ds = FlatPopcount::<_, ()>::new(Rc::clone(&shared));
+/// assert_eq!(1, rank_ds.rank::<true>(20));
+/// drop(rank_ds);
+///
+/// // Owning the bit vector
+/// let rank_ds = FlatPopcount::<_, ()>::new(Rc::try_unwrap(shared).unwrap());
+/// assert_eq!(1, rank_ds.rank::<true>(20));
+/// let bv: BitVec = rank_ds.into_inner();
+///
+/// // Using a slice of the bit vector
+/// let rank_ds = FlatPopcount::<_, ()>::new(bv.slice(5..));
+/// assert_eq!(1, rank_ds.rank::<true>(20));
+///
+/// // Using raw words
+/// let rank_ds = FlatPopcount::<_, ()>::new(vec![0b1011u64, 1]);
+/// assert_eq!(4, rank_ds.rank::<true>(65));
+/// ```
+///
+/// The L1/L2 index can also be borrowed from a buffer such as a memory mapped file using
+/// [`FlatPopcount::from_bytes`]. See [`FlatPopcountView`].
+///
+/// If the data structure owns its backing, bits can be changed through [`BitModify`], which
+/// updates the index instead of rebuilding it.
+pub struct FlatPopcount<Backing, Strat = LinearSearch, Index = Vec<u128>> {
+    backing: Backing,
+    l1_index: Index,
     sampled_ones: IntVec,
+    sampled_zeros: IntVec,
     number_of_ones: usize,
     _mark: PhantomData<Strat>,
 }
 
-impl<'a, T> FlatPopcount<'a, T> {
+/// A read-only [`FlatPopcount`] over a bit vector and an index that are both borrowed from
+/// buffers without copying, e.g. from memory mapped files.
+///
+/// It is created using [`BitSlice::from_bytes`] and [`FlatPopcount::from_bytes`].
+pub type FlatPopcountView<'a, Strat = LinearSearch> =
+    FlatPopcount<BitSlice<&'a [Word]>, Strat, &'a [u128]>;
+
+impl<Backing: WordAccess, T> FlatPopcount<Backing, T> {
     /// Creates a new rank data structure from a bit vector.
     ///
     /// # Arguments
     ///
-    /// * `backing` - The backing bitvector
+    /// * `backing` - The backing bit storage
     ///
     /// # Examples
     ///
@@ -58,86 +120,370 @@ impl<'a, T> FlatPopcount<'a, T> {
     /// bv.flip(15);
     /// bv.flip(20);
     ///
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     /// assert_eq!(2, rank_ds.rank::<true>(17));
     /// assert_eq!(12, rank_ds.rank::<false>(13));
     /// ```
-    pub fn new(backing: &'a BitVec) -> Self {
-        if backing.len() == 0 {
+    pub fn new(backing: Backing) -> Self {
+        if backing.bit_len() == 0 {
             return Self {
                 backing,
                 l1_index: Vec::with_capacity(0),
                 sampled_ones: IntVec::new(1),
+                sampled_zeros: IntVec::new(1),
                 _mark: Default::default(),
                 number_of_ones: 0
             }
         }
 
-        let n = backing.len();
+        let n = backing.bit_len();
         let log_n = n.ilog2() as usize + 1;
+        let (l1_index, number_of_ones) = Self::build_indices(&backing);
         let mut temp = Self {
             backing,
-            l1_index: Vec::with_capacity((n as f64 / L1_BLOCK_SIZE as f64).ceil() as usize + 1),
+            l1_index,
             sampled_ones: IntVec::new(log_n),
+            sampled_zeros: IntVec::new(log_n),
             _mark: Default::default(),
-            number_of_ones: 0
+            number_of_ones,
         };
-        temp.build_indices();
         temp.sample_ones();
+        temp.sample_zeros();
         temp
     }
 
-    /// Builds the required backing index data structure.
-    fn build_indices(&mut self) {
+    /// Builds the L1/L2 index for the given backing.
+    ///
+    /// returns: The L1 index entries and the number of ones in the backing.
+    fn build_indices(backing: &Backing) -> (Vec<u128>, usize) {
+        let n = backing.bit_len();
+        if n == 0 {
+            return (Vec::with_capacity(0), 0);
+        }
+        let word_len = backing.word_len();
+        // The last L1 entry always has an unused L2 entry, so a full last L1 block is followed by
+        // an additional entry
+        let l1_len = word_len.div_ceil(8) / 8 + 1;
+        let mut l1_index = Vec::with_capacity(n.div_ceil(L1_BLOCK_SIZE) + 1);
         let mut num_ones = 0;
-        let mut ones_in_l1 = 0;
-        let raw_bv = self.backing.raw();
-
-        let mut current_l1 = 0u128;
-        let mut i = 0;
-        for l2_block in raw_bv.chunks(8) {
-            let offset = i & 0b0111;
-            // In this case this is the last L2 block of this L1 block and we don't store its
-            // popcount explicitly
-            if offset == 7 {
-                // Push the L1 Index entry along with its 7 L2 Index entries to the index
-                self.l1_index.push(current_l1);
-
-                // Add the number of ones that occurred in this l1 block
-                num_ones += ones_in_l1
-                    + l2_block.iter().copied().map(usize::count_ones).sum::<u32>() as usize;
-                current_l1 = (num_ones as u128) << 84;
-                ones_in_l1 = 0;
-                i += 1;
-                continue;
+        // The kernel is selected once, since it is used for every L1 block
+        let count_l2_blocks = popcount::block_kernel();
+        let mut block = [0; popcount::BLOCK_WORDS];
+
+        for l1 in 0..l1_len {
+            let start = l1 * popcount::BLOCK_WORDS;
+            let end = word_len.min(start + popcount::BLOCK_WORDS);
+            let mut current_l1 = (num_ones as u128) << 84;
+            if start >= end {
+                // Fill the unused L2 blocks in the last L1 Block with all ones
+                for offset in 0..7 {
+                    current_l1 |= L2_INDEX_MASK << (12 * (6 - offset));
+                }
+                l1_index.push(current_l1);
+                break;
             }
-            // Add the L2 index entry
-            ones_in_l1 += l2_block.iter().copied().map(usize::count_ones).sum::<u32>() as usize;
-            current_l1 |= (ones_in_l1 as u128 & L2_INDEX_MASK) << (12 * (6 - offset));
-            i += 1;
-        }
-        // Fill the unused L2 blocks in the last L1 Block with all ones
-        while i & 0b0111 != 7 {
-            current_l1 |= L2_INDEX_MASK << (12 * (6 - (i & 0b0111)));
-            i += 1;
+
+            // Words past the end of the backing are counted as zeros
+            for (i, word) in block.iter_mut().enumerate() {
+                // SAFETY: The word is in bounds
+                *word = if start + i < end {
+                    unsafe { backing.get_word_unchecked(start + i) }
+                } else {
+                    0
+                };
+            }
+            // SAFETY: The kernel was selected by block_kernel, so the CPU supports it
+            let counts = unsafe { count_l2_blocks(&block) };
+
+            // The popcount of the last L2 block of this L1 block is not stored explicitly
+            let mut ones_in_l1 = 0;
+            for (offset, &count) in counts[..7].iter().enumerate() {
+                ones_in_l1 += count;
+                let l2 = if start + offset * 8 < end {
+                    ones_in_l1 as u128
+                } else {
+                    L2_INDEX_MASK
+                };
+                current_l1 |= l2 << (12 * (6 - offset));
+            }
+            l1_index.push(current_l1);
+            num_ones += ones_in_l1 + counts[7];
         }
-        self.l1_index.push(current_l1);
+        (l1_index, num_ones)
     }
 
     /// Samples every 8192nd one and saves the l1 block it is in
     fn sample_ones(&mut self) {
-        let mut count = -1isize;
-        for (i, value) in self.backing.iter().enumerate() {
+        for (count, i) in BitPositions::<_, true>::new(&self.backing).enumerate() {
+            if count & ((1 << 13) - 1) == 0 {
+                self.sampled_ones.push(i >> 13);
+            }
+        }
+    }
+
+    /// Samples every 8192nd zero and saves the l1 block it is in
+    fn sample_zeros(&mut self) {
+        for (count, i) in BitPositions::<_, false>::new(&self.backing).enumerate() {
+            if count & ((1 << 13) - 1) == 0 {
+                self.sampled_zeros.push(i >> 13);
+            }
+        }
+    }
+
+    /// Reads an index written by [`FlatPopcount::write_to`] and attaches it to the given backing.
+    ///
+    /// The index is checked for consistency with itself and the length of the backing, which
+    /// takes time linear in the size of the index and does not touch the bits. Therefore, a
+    /// corrupted index results in an error instead of out of bounds accesses. An index written
+    /// for different bits of the same length may still result in incorrect query results, which
+    /// can be detected with [`FlatPopcount::verify`].
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The backing bit storage the index was built for.
+    /// * `reader` - The reader to read the index from.
+    ///
+    /// returns: The rank/select data structure or an error if reading failed, the data is
+    /// malformed, or it does not match the backing.
+    #[cfg(feature = "std")]
+    pub fn read_from(backing: Backing, reader: &mut impl Read) -> Result<Self, SerializeError> {
+        let (number_of_ones, l1_len) = read_index_header(reader, &backing)?;
+        let l1_index = serialize::read_words(reader, 2 * l1_len)?
+            .chunks_exact(2)
+            .map(|entry| entry[0] as u128 | (entry[1] as u128) << 64)
+            .collect::<Vec<_>>();
+        check_index(&l1_index, backing.word_len(), number_of_ones)?;
+        let (sampled_ones, sampled_zeros) =
+            read_samples(reader, &l1_index, backing.bit_len(), number_of_ones)?;
+
+        Ok(Self {
+            backing,
+            l1_index,
+            sampled_ones,
+            sampled_zeros,
+            number_of_ones,
+            _mark: Default::default(),
+        })
+    }
+}
+
+impl<Backing: WordAccess + BitModify, T> FlatPopcount<Backing, T> {
+    /// Gets the bit at the given index from the words of the backing.
+    ///
+    /// # Safety
+    ///
+    /// The index must be in bounds of the backing.
+    #[inline]
+    unsafe fn backing_bit_unchecked(&self, index: usize) -> bool {
+        (self.backing.get_word_unchecked(index >> WORD_EXP) >> (index & WORD_MASK)) & 1 == 1
+    }
+
+    /// Updates the index after the bit at the given index has been changed to `value`.
+    ///
+    /// This adjusts the L2 entries of the L1 block containing the bit, the cumulative counts of
+    /// all following L1 blocks and the select samples whose targets moved. It does not touch the
+    /// bits themselves, so it is much cheaper than rebuilding the index.
+    fn update_index(&mut self, index: usize, value: bool) {
+        let l1_index = index >> L1_BLOCK_SIZE_EXP;
+        let l2_index = (index >> L2_BLOCK_SIZE_EXP) & 0b0111;
+        let word_len = self.backing.word_len();
+        let apply = |entry: &mut u128, delta: u128| {
             if value {
-                count += 1;
-                if count & ((1 << 13) - 1) == 0 {
-                    self.sampled_ones.push(i as usize >> 13);
-                }
+                *entry += delta
+            } else {
+                *entry -= delta
             }
+        };
+
+        // The L2 entries store the cumulative counts up to the end of each L2 block. Entries of
+        // L2 blocks past the end of the backing are padding and must stay unchanged.
+        let entry = &mut self.l1_index[l1_index];
+        for i in l2_index..7 {
+            if (l1_index << 6) + (i << 3) < word_len {
+                apply(entry, 1 << (12 * (6 - i)));
+            }
+        }
+        for entry in &mut self.l1_index[l1_index + 1..] {
+            apply(entry, 1 << 84);
+        }
+        if value {
+            self.number_of_ones += 1;
+        } else {
+            self.number_of_ones -= 1;
         }
-        self.number_of_ones = (count + 1) as usize;
+
+        // Only the samples of ones and zeroes after the changed bit can have moved
+        let ones = self.rank::<true>(index);
+        let zeros = self.len() - self.number_of_ones;
+        resample::<true>(
+            &mut self.sampled_ones,
+            &self.l1_index,
+            self.number_of_ones,
+            ones >> 13,
+        );
+        resample::<false>(
+            &mut self.sampled_zeros,
+            &self.l1_index,
+            zeros,
+            (index - ones) >> 13,
+        );
     }
+}
 
+/// Allows modifying the bits of a [`FlatPopcount`] that owns its backing. The index is updated
+/// incrementally, so rank and select queries stay correct without rebuilding it.
+///
+/// Each modification takes time linear in the size of the index after the modified bit, which is
+/// less than 4% of the bits, without scanning the bits themselves.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::{
+///     bit_vec::{BitModify, BitVec},
+///     rank_select::{FlatPopcount, RankSupport, SelectSupport}
+/// };
+///
+/// let bv: BitVec = (0..10000).map(|i| i % 3 == 0).collect();
+/// let mut rank_ds = FlatPopcount::<_>::new(bv);
+/// assert_eq!(34, rank_ds.rank::<true>(100));
+///
+/// rank_ds.set_bit(50, true);
+/// rank_ds.flip_bit(0);
+/// assert_eq!(34, rank_ds.rank::<true>(100));
+/// assert_eq!(Some(50), SelectSupport::<true>::select(&rank_ds, 16));
+/// ```
+impl<Backing: WordAccess + BitModify, T> BitModify for FlatPopcount<Backing, T> {
+    unsafe fn set_bit_unchecked(&mut self, index: usize, value: bool) {
+        let old = self.backing_bit_unchecked(index);
+        if old != value {
+            self.backing.set_bit_unchecked(index, value);
+            self.update_index(index, value);
+        }
+    }
+
+    fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        // SAFETY: The index is in bounds
+        unsafe { self.set_bit_unchecked(index, value) }
+    }
+
+    unsafe fn flip_bit_unchecked(&mut self, index: usize) {
+        let old = self.backing_bit_unchecked(index);
+        self.backing.flip_bit_unchecked(index);
+        self.update_index(index, !old);
+    }
+
+    fn flip_bit(&mut self, index: usize) {
+        if index >= self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        // SAFETY: The index is in bounds
+        unsafe { self.flip_bit_unchecked(index) }
+    }
+}
+
+impl<'a, Backing: WordAccess, T> FlatPopcount<Backing, T, &'a [u128]> {
+    /// Creates a rank/select data structure whose L1/L2 index is borrowed from a buffer
+    /// containing an index written by [`FlatPopcount::write_to`], without copying it.
+    ///
+    /// This is intended for memory-mapped files. Only the select samples, which are small
+    /// compared to the rest of the index, are copied. The buffer must contain exactly one index
+    /// and must be aligned to 16 bytes, which is always the case for memory mapped files.
+    ///
+    /// Like in [`FlatPopcount::read_from`], the index is checked for consistency with itself and
+    /// the length of the backing in time linear in the size of the index, without touching the
+    /// bits. This guarantees that queries never access the backing out of bounds, so this is
+    /// safe to use on untrusted buffers. It does not guarantee correct query results if the
+    /// index was written for different bits of the same length. Use [`FlatPopcount::verify`] to
+    /// check this as well, which requires a pass over all bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The backing bit storage the index was built for.
+    /// * `bytes` - The buffer containing the index.
+    ///
+    /// returns: The rank/select data structure or an error if the buffer is misaligned,
+    /// malformed, or does not match the backing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use succinct_neo::{
+    ///     bit_vec::{BitVec, BitSlice},
+    ///     rank_select::{FlatPopcountView, RankSupport}
+    /// };
+    /// # use succinct_neo::rank_select::FlatPopcount;
+    ///
+    /// # let bv: BitVec = (0..10000).map(|i| i % 3 == 0).collect();
+    /// # let mut bv_buf = Vec::new();
+    /// # bv.write_to(&mut bv_buf).unwrap();
+    /// # let mut index_buf = Vec::new();
+    /// # FlatPopcount::<_>::new(&bv).write_to(&mut index_buf).unwrap();
+    /// # fn aligned(bytes: &[u8]) -> &'static [u8] {
+    /// #     let words = vec![0u128; bytes.len().div_ceil(16)].leak();
+    /// #     let words = unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), bytes.len()) };
+    /// #     words.copy_from_slice(bytes);
+    /// #     words
+    /// # }
+    /// // These would usually be memory mapped files
+    /// let (bv_bytes, index_bytes) = (aligned(&bv_buf), aligned(&index_buf));
+    ///
+    /// let bits = BitSlice::from_bytes(bv_bytes).unwrap();
+    /// let rank_ds: FlatPopcountView = FlatPopcountView::from_bytes(bits, index_bytes).unwrap();
+    /// assert_eq!(34, rank_ds.rank::<true>(100));
+    /// # }
+    /// ```
+    pub fn from_bytes(backing: Backing, bytes: &'a [u8]) -> Result<Self, SerializeError> {
+        // SAFETY: The index is checked for consistency right after
+        let pop = unsafe { Self::from_bytes_unchecked(backing, bytes)? };
+        check_index(pop.l1_index, pop.backing.word_len(), pop.number_of_ones)?;
+        Ok(pop)
+    }
+
+    /// Creates a rank/select data structure whose L1/L2 index is borrowed from a buffer
+    /// like [`FlatPopcount::from_bytes`], but without checking the consistency of the L1/L2
+    /// index.
+    ///
+    /// This runs in time proportional to the size of the select samples. The format, alignment
+    /// and lengths are still checked.
+    ///
+    /// # Safety
+    ///
+    /// The index must have been written by [`FlatPopcount::write_to`] for a data structure over
+    /// the same bits as `backing`. Otherwise, queries may access the backing out of bounds.
+    pub unsafe fn from_bytes_unchecked(
+        backing: Backing,
+        bytes: &'a [u8],
+    ) -> Result<Self, SerializeError> {
+        let mut reader = bytes;
+        let (number_of_ones, l1_len) = read_index_header(&mut reader, &backing)?;
+        if reader.len() < 16 * l1_len {
+            return Err(SerializeError::Invalid("buffer too short for the L1 index"));
+        }
+        let (l1_bytes, mut reader) = reader.split_at(16 * l1_len);
+        let l1_index = serialize::cast_slice::<u128>(l1_bytes)?;
+        let (sampled_ones, sampled_zeros) =
+            read_samples(&mut reader, l1_index, backing.bit_len(), number_of_ones)?;
+        if !reader.is_empty() {
+            return Err(SerializeError::Invalid("trailing bytes after the index"));
+        }
+
+        Ok(Self {
+            backing,
+            l1_index,
+            sampled_ones,
+            sampled_zeros,
+            number_of_ones,
+            _mark: Default::default(),
+        })
+    }
+}
+
+impl<Backing: WordAccess, T, Index: AsRef<[u128]>> FlatPopcount<Backing, T, Index> {
     /// Gets the number of bits in the underlying bit vector.
     ///
     /// This is *not* the number of ones in the bit vector.
@@ -151,12 +497,12 @@ impl<'a, T> FlatPopcount<'a, T> {
     /// };
     ///
     /// let bv = BitVec::new(64);
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     /// assert_eq!(bv.len(), rank_ds.len());
     /// ```
     #[inline]
     pub fn len(&self) -> usize {
-        self.backing.len()
+        self.backing.bit_len()
     }
 
     /// Returns `true`, if the backing bit vector is empty.
@@ -172,11 +518,11 @@ impl<'a, T> FlatPopcount<'a, T> {
     /// };
     ///
     /// let bv = BitVec::new(64);
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     /// assert!(!rank_ds.is_empty());
     ///
     /// let bv = BitVec::new(0);
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     /// assert!(rank_ds.is_empty());
     /// ```
     #[must_use]
@@ -185,22 +531,147 @@ impl<'a, T> FlatPopcount<'a, T> {
         self.len() == 0
     }
 
+    /// Grants access to the backing bit vector of this data structure.
+    #[inline]
+    pub fn backing(&self) -> &Backing {
+        &self.backing
+    }
+
+    /// Destroys this data structure and returns the backing bit vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::{
+    ///     bit_vec::{BitGet, BitVec},
+    ///     rank_select::FlatPopcount
+    /// };
+    ///
+    /// let mut bv = BitVec::new(64);
+    /// bv.flip(10);
+    ///
+    /// let rank_ds = FlatPopcount::<_, ()>::new(bv);
+    /// let bv = rank_ds.into_inner();
+    /// assert!(bv.get_bit(10));
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> Backing {
+        self.backing
+    }
+
+    /// Writes the index of this data structure in a binary format which can be read with
+    /// [`FlatPopcount::read_from`].
+    ///
+    /// This only writes the L1/L2 index and the select samples, but *not* the backing bits, which
+    /// need to be persisted separately (e.g. using [`BitVec::write_to`]).
+    /// All values are stored in little-endian byte order. See the [`serialize`]
+    /// module for details.
+    ///
+    /// [`BitVec::write_to`]: crate::bit_vec::BitVec::write_to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::{
+    ///     bit_vec::BitVec,
+    ///     rank_select::{FlatPopcount, RankSupport, SelectSupport}
+    /// };
+    ///
+    /// let bv: BitVec = (0..10000).map(|i| i % 3 == 0).collect();
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
+    ///
+    /// let (mut bv_buf, mut index_buf) = (Vec::new(), Vec::new());
+    /// bv.write_to(&mut bv_buf).unwrap();
+    /// rank_ds.write_to(&mut index_buf).unwrap();
+    ///
+    /// let bv = BitVec::read_from(&mut bv_buf.as_slice()).unwrap();
+    /// let rank_ds = FlatPopcount::<_, ()>::read_from(bv, &mut index_buf.as_slice()).unwrap();
+    /// assert_eq!(34, rank_ds.rank::<true>(100));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        serialize::write_header(writer, FLAT_POPCOUNT_MAGIC)?;
+        serialize::write_u64(writer, self.len() as u64)?;
+        serialize::write_u64(writer, self.number_of_ones as u64)?;
+        serialize::write_u64(writer, self.l1_index.as_ref().len() as u64)?;
+        // Each entry is written as its lower and then its upper half, which is exactly the
+        // little-endian representation of the entry
+        let words = self
+            .l1_index
+            .as_ref()
+            .iter()
+            .flat_map(|&entry| [entry as Word, (entry >> 64) as Word])
+            .collect::<Vec<_>>();
+        serialize::write_words(writer, &words)?;
+        self.sampled_ones.write_to(writer)?;
+        self.sampled_zeros.write_to(writer)
+    }
+
+    /// Gets the number of ones in the underlying bit vector.
+    #[inline]
+    pub fn number_of_ones(&self) -> usize {
+        self.number_of_ones
+    }
+
+    /// Checks that the L1/L2 index and the number of ones match the bits of the backing by
+    /// rebuilding the index, which requires a pass of popcounts over all bits.
+    ///
+    /// This is only useful for an index read using [`FlatPopcount::read_from`], which checks the
+    /// index for consistency but not whether it was written for the same bits.
+    pub fn verify(&self) -> bool {
+        let (expected, ones) = FlatPopcount::<Backing, T>::build_indices(&self.backing);
+        ones == self.number_of_ones && expected == self.l1_index.as_ref()
+    }
+
+    /// Gets an iterator over the positions of all ones in the underlying bit vector.
+    ///
+    /// Since the number of ones is known, the iterator implements [`ExactSizeIterator`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::{
+    ///     bit_vec::BitVec,
+    ///     rank_select::FlatPopcount
+    /// };
+    ///
+    /// let mut bv = BitVec::new(100);
+    /// bv.flip(10);
+    /// bv.flip(80);
+    ///
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
+    /// let ones = rank_ds.iter_ones();
+    ///
+    /// assert_eq!(2, ones.len());
+    /// assert_eq!(vec![10, 80], ones.collect::<Vec<_>>());
+    /// ```
+    pub fn iter_ones(&self) -> ExactBitPositions<&Backing, true> {
+        BitPositions::new(&self.backing).with_count(self.number_of_ones)
+    }
+
+    /// Gets an iterator over the positions of all zeroes in the underlying bit vector.
+    ///
+    /// Since the number of zeroes is known, the iterator implements [`ExactSizeIterator`].
+    pub fn iter_zeros(&self) -> ExactBitPositions<&Backing, false> {
+        BitPositions::new(&self.backing).with_count(self.len() - self.number_of_ones)
+    }
+
     /// Calculates the number of ones up to and not including the given l2 block.
     ///
     /// # Arguments
     ///
     /// * `l1_index` - The index of the L1 Block
     /// * `l2_index` - The index of the L2 Block inside of the L1 Block (valid range is 0-7
-    /// inclusively)
+    ///   inclusively)
     #[inline]
     fn rough_rank_1(&self, l1_index: usize, l2_index: usize) -> usize {
         (if l2_index == 0 {
-            let entry = unsafe { *self.l1_index.get_unchecked(l1_index) };
+            let entry = unsafe { *self.l1_index.as_ref().get_unchecked(l1_index) };
             entry >> 84
         } else {
             let offset_blocks = 7 - l2_index;
             let offset = 12 * offset_blocks;
-            let entry = unsafe { *self.l1_index.get_unchecked(l1_index) };
+            let entry = unsafe { *self.l1_index.as_ref().get_unchecked(l1_index) };
             let l1 = entry >> 84;
             let l2 = (entry >> offset) & L2_INDEX_MASK;
             l1 + l2
@@ -209,12 +680,36 @@ impl<'a, T> FlatPopcount<'a, T> {
 
     #[inline]
     unsafe fn l1(&self, l1_index: usize) -> usize {
-        *((self.l1_index.get_unchecked(l1_index) as *const u128 as *const usize).offset(1)) >> 20
+        (*self.l1_index.as_ref().get_unchecked(l1_index) >> 84) as usize
+    }
+
+    /// Calculates the number of ones or zeroes up to and not including the given l1 block.
+    ///
+    /// # Safety
+    ///
+    /// The l1 index must be in bounds of the l1 index.
+    #[inline]
+    unsafe fn l1_rank<const TARGET: bool>(&self, l1_index: usize) -> usize {
+        let ones = self.l1(l1_index);
+        if TARGET {
+            ones
+        } else {
+            (l1_index << L1_BLOCK_SIZE_EXP) - ones
+        }
     }
 }
 
-impl<T> RankSupport for FlatPopcount<'_, T> {
+impl<Backing: WordAccess, T, Index: AsRef<[u128]>> RankSupport
+    for FlatPopcount<Backing, T, Index>
+{
     fn rank<const TARGET: bool>(&self, index: usize) -> usize {
+        if index > self.len() {
+            panic!("length is {} but index is {index}", self.len())
+        }
+        // An empty backing has no index entries at all
+        if self.l1_index.as_ref().is_empty() {
+            return 0;
+        }
         let l1_index = index >> L1_BLOCK_SIZE_EXP;
         let l2_index = (index >> L2_BLOCK_SIZE_EXP) & 0b0111;
         // The index inside of the L2 block
@@ -227,16 +722,20 @@ impl<T> RankSupport for FlatPopcount<'_, T> {
         let rest_bits = internal_index - (full_remaining_words << 6);
 
         let mut ones = self.rough_rank_1(l1_index, l2_index);
-        let raw_backing = self.backing.raw();
         let word_start = (l1_index << 6) + (l2_index << 3);
-        for i in 0..full_remaining_words {
-            ones += unsafe { raw_backing.get_unchecked(word_start + i).count_ones() as usize };
-        }
+        ones += popcount::count_ones_few(
+            (word_start..word_start + full_remaining_words)
+                // SAFETY: All words before the requested index are in bounds
+                .map(|i| unsafe { self.backing.get_word_unchecked(i) }),
+        );
 
-        // Add the rest bits
-        unsafe {
-            ones += (raw_backing.get_unchecked(word_start + full_remaining_words) & ((1 << rest_bits) - 1))
-                .count_ones() as usize
+        // Add the rest bits. If there are none, the word might be past the end of the backing.
+        if rest_bits > 0 {
+            unsafe {
+                ones += (self.backing.get_word_unchecked(word_start + full_remaining_words)
+                    & ((1 << rest_bits) - 1))
+                    .count_ones() as usize
+            }
         }
 
         if TARGET {
@@ -247,75 +746,273 @@ impl<T> RankSupport for FlatPopcount<'_, T> {
     }
 }
 
-impl<Strat: SelectStrategy> SelectSupport<true> for FlatPopcount<'_, Strat> {
-    fn select(&self, mut rank: usize) -> Option<usize> {
-        if rank > self.number_of_ones {
+impl<Backing: WordAccess, Strat: SelectStrategy, Index: AsRef<[u128]>>
+    FlatPopcount<Backing, Strat, Index>
+{
+    /// Finds the position of the one or zero with the given rank.
+    ///
+    /// # Generic Arguments
+    ///
+    /// * `TARGET` - `true` if a one should be searched, `false` if a zero should be searched.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank` - The rank of the zero/one to find. The first zero/one has rank `0`.
+    fn select_impl<const TARGET: bool>(&self, mut rank: usize) -> Option<usize> {
+        let total = if TARGET {
+            self.number_of_ones
+        } else {
+            self.len() - self.number_of_ones
+        };
+        if rank >= total {
             return None;
         }
-        let mut l1_index = self.sampled_ones.get(rank >> 13);
-        // SAFETY: The data in sampled_ones should be correct, so this must work too
-        let mut l1 = unsafe { self.l1(l1_index) };
-        // Find the l1 block that contains the 1 we need
-        while l1 <= rank && l1_index < self.l1_index.len() {
+
+        let samples = if TARGET {
+            &self.sampled_ones
+        } else {
+            &self.sampled_zeros
+        };
+        let mut l1_index = samples.get(rank >> 13);
+        // Find the l1 block that contains the bit we need
+        // SAFETY: We check that the next l1 block is in bounds before accessing it
+        while l1_index + 1 < self.l1_index.as_ref().len()
+            && unsafe { self.l1_rank::<TARGET>(l1_index + 1) } <= rank
+        {
             l1_index += 1;
-            l1 = unsafe { self.l1(l1_index) };
         }
-        l1_index -= 1;
-        rank -= unsafe { self.l1(l1_index) };
+        rank -= unsafe { self.l1_rank::<TARGET>(l1_index) };
 
         // Find the correct l2 block inside the l1 block
-        let block = unsafe { *self.l1_index.get_unchecked(l1_index) };
-        let (l2_index, ones_in_l2) = Strat::find_l2(block, rank);
-        rank -= ones_in_l2;
+        let block = unsafe { *self.l1_index.as_ref().get_unchecked(l1_index) };
+        let (l2_index, count_in_l2) = if TARGET {
+            Strat::find_l2(block, rank)
+        } else {
+            Strat::find_l2_zeros(block, rank)
+        };
+        rank -= count_in_l2;
 
         // Find the correct word inside the l2 block
         let mut current_index = (l1_index << 6) + (l2_index << 3);
-        let mut index_in_l2 = 0;
+        let word_len = self.backing.word_len();
+        let mut word;
         loop {
-            let num_ones = unsafe { self.backing.raw().get_unchecked(current_index).count_ones() as usize};
-            if num_ones <= rank {
-                rank -= num_ones;
-                current_index += 1;
-                index_in_l2 += 1;
-            } else {
-                break
+            // The bit we search for is in this l2 block, unless the index was read for different
+            // bits. In that case, we must not leave the bit vector.
+            if current_index >= word_len {
+                return None;
+            }
+            // SAFETY: We checked that the word is in bounds
+            word = unsafe { self.backing.get_word_unchecked(current_index) };
+            if !TARGET {
+                word = !word;
             }
+            let count = word.count_ones() as usize;
+            if count > rank {
+                break;
+            }
+            rank -= count;
+            current_index += 1;
         }
 
-        // Find the correct 1 inside the word
-        let word = unsafe { *self.backing.raw().get_unchecked(current_index) };
-        let mut index_in_word = 0;
-        loop {
-            let bit = unsafe { word.get_bit_unchecked(index_in_word) };
-            if rank == 0 && bit {
-                break;
+        // Find the correct bit inside the word by clearing all lower set bits
+        for _ in 0..rank {
+            word &= word - 1;
+        }
+
+        Some((current_index << 6) + word.trailing_zeros() as usize)
+    }
+}
+
+impl<Backing: WordAccess, Strat: SelectStrategy, Index: AsRef<[u128]>> SelectSupport<true>
+    for FlatPopcount<Backing, Strat, Index>
+{
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.select_impl::<true>(rank)
+    }
+}
+
+impl<Backing: WordAccess, Strat: SelectStrategy, Index: AsRef<[u128]>> SelectSupport<false>
+    for FlatPopcount<Backing, Strat, Index>
+{
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.select_impl::<false>(rank)
+    }
+}
+
+/// Recomputes the select samples of ones or zeroes starting with the given sample, after the
+/// number of ones or zeroes before some L1 blocks changed.
+///
+/// Each sample is the position of its target divided by $2^{13}$. Since this only depends on the
+/// L1 block containing the target, the samples are found by binary searching the L1 index.
+///
+/// # Arguments
+///
+/// * `samples` - The samples to update.
+/// * `l1_index` - The updated L1 index.
+/// * `count` - The updated number of ones or zeroes.
+/// * `first` - The first sample that may have changed.
+fn resample<const TARGET: bool>(
+    samples: &mut IntVec,
+    l1_index: &[u128],
+    count: usize,
+    first: usize,
+) {
+    let rank_before = |l1: usize| {
+        let ones = (l1_index[l1] >> 84) as usize;
+        if TARGET {
+            ones
+        } else {
+            (l1 << L1_BLOCK_SIZE_EXP) - ones
+        }
+    };
+    let len = count.div_ceil(1 << 13);
+    let old_len = samples.len();
+    samples.truncate(len);
+    for i in first..len {
+        // Find the last L1 block with at most i * 8192 ones or zeroes before it
+        let (mut low, mut high) = (0, l1_index.len());
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if rank_before(mid) <= i << 13 {
+                low = mid;
+            } else {
+                high = mid;
             }
-            // SAFETY: indices are <= 64
-            if bit {
-                rank -= 1;
+        }
+        if i < old_len {
+            samples.set(i, low >> 1);
+        } else {
+            samples.push(low >> 1);
+        }
+    }
+}
+
+/// Reads the header of a serialized index and checks it against the backing.
+///
+/// returns: The number of ones and the number of L1 index entries.
+fn read_index_header(
+    reader: &mut impl ReadBytes,
+    backing: &impl WordAccess,
+) -> Result<(usize, usize), SerializeError> {
+    serialize::read_header(reader, FLAT_POPCOUNT_MAGIC)?;
+    let len = serialize::read_usize(reader)?;
+    if len != backing.bit_len() {
+        return Err(SerializeError::Invalid("length does not match the backing"));
+    }
+    let number_of_ones = serialize::read_usize(reader)?;
+    if number_of_ones > len {
+        return Err(SerializeError::Invalid("more ones than bits"));
+    }
+    // There is one L1 entry per 64 words and the last one is always followed by another one
+    let expected_l1_len = if len == 0 {
+        0
+    } else {
+        backing.word_len().div_ceil(8) / 8 + 1
+    };
+    if serialize::read_usize(reader)? != expected_l1_len {
+        return Err(SerializeError::Invalid("wrong number of L1 index entries"));
+    }
+    Ok((number_of_ones, expected_l1_len))
+}
+
+/// Checks that a serialized L1/L2 index is consistent with itself, the number of words of the
+/// backing and the number of ones, without looking at the bits.
+///
+/// The L1 entries must start at zero and increase by at most the size of an L1 block, and the
+/// cumulative L2 counts inside each L1 entry must increase by at most the size of an L2 block.
+/// The unused L2 entries of the last L1 entry must be padding, and the last L1 entry together
+/// with its L2 counts must add up to the number of ones. The number of entries is already checked
+/// by [`read_index_header`].
+fn check_index(
+    l1_index: &[u128],
+    word_len: usize,
+    number_of_ones: usize,
+) -> Result<(), SerializeError> {
+    let l2_blocks = word_len.div_ceil(8);
+    let l1_ones = |l1: usize| l1_index[l1] >> 84;
+    let consistent = l1_index.iter().enumerate().all(|(l1, &entry)| {
+        let used = (l2_blocks - 8 * l1).min(8);
+        // The cumulative number of ones in the L1 block up to the end of the current L2 block
+        let mut ones = 0;
+        for l2 in 0..7 {
+            let count = (entry >> (12 * (6 - l2))) & L2_INDEX_MASK;
+            if l2 >= used {
+                if count != L2_INDEX_MASK {
+                    return false;
+                }
+            } else if count < ones || count - ones > 1 << L2_BLOCK_SIZE_EXP {
+                return false;
+            } else {
+                ones = count;
             }
-            index_in_word += 1;
-        };
+        }
+        if l1 + 1 == l1_index.len() {
+            return l1_ones(l1) + ones == number_of_ones as u128;
+        }
+        // The last L2 block of the L1 block has no explicit entry
+        let in_l1 = l1_ones(l1 + 1).checked_sub(l1_ones(l1));
+        in_l1.is_some_and(|in_l1| in_l1 >= ones && in_l1 - ones <= 1 << L2_BLOCK_SIZE_EXP)
+    });
+    if !consistent || l1_index.first().is_some_and(|&entry| entry >> 84 != 0) {
+        return Err(SerializeError::Invalid("inconsistent L1/L2 index"));
+    }
+    Ok(())
+}
 
-        Some((l1_index << L1_BLOCK_SIZE_EXP) + (l2_index << L2_BLOCK_SIZE_EXP) + (index_in_l2 << 6) + index_in_word)
+/// Reads the select samples of a serialized index and checks them against the L1 index.
+fn read_samples(
+    reader: &mut impl ReadBytes,
+    l1_index: &[u128],
+    len: usize,
+    number_of_ones: usize,
+) -> Result<(IntVec, IntVec), SerializeError> {
+    let sampled_ones = IntVec::read_bytes(reader)?;
+    let sampled_zeros = IntVec::read_bytes(reader)?;
+    // The sample of the i-th one or zero must not point past the L1 block containing it
+    let check_samples = |samples: &IntVec, count: usize, rank: &dyn Fn(usize) -> usize| {
+        samples.len() == count.div_ceil(1 << 13)
+            && samples
+                .iter()
+                .enumerate()
+                .all(|(i, sample)| sample < l1_index.len() && rank(sample) <= i << 13)
+    };
+    let l1_ones = |l1: usize| (l1_index[l1] >> 84) as usize;
+    if !check_samples(&sampled_ones, number_of_ones, &l1_ones)
+        || !check_samples(&sampled_zeros, len - number_of_ones, &|l1| {
+            (l1 << L1_BLOCK_SIZE_EXP).saturating_sub(l1_ones(l1))
+        })
+    {
+        return Err(SerializeError::Invalid("invalid select samples"));
     }
+    Ok((sampled_ones, sampled_zeros))
 }
 
 #[cfg(test)]
 mod test {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
     use crate::{bit_vec::BitVec, rank_select::traits::RankSupport};
     use crate::int_vec::IntAccess;
-    use crate::rank_select::flat_popcount::BinarySearch;
+    use crate::rank_select::flat_popcount::{
+        BinarySearch, LinearSearch, RuntimeSimdSearch, SelectStrategy,
+    };
     use crate::rank_select::traits::SelectSupport;
+    use crate::bit_vec::{BitModify, WordAccess};
+    #[cfg(feature = "std")]
+    use crate::{bit_vec::BitSlice, serialize::{AlignedBuf, SerializeError}};
     use super::{FlatPopcount, L2_INDEX_MASK};
+    #[cfg(feature = "std")]
+    use super::FlatPopcountView;
 
     #[inline]
-    fn l1(pop: &FlatPopcount, index: usize) -> usize {
+    fn l1<B, S>(pop: &FlatPopcount<B, S>, index: usize) -> usize {
         (&pop.l1_index[index] >> 84) as usize
     }
 
     #[inline]
-    fn l2(pop: &FlatPopcount, l1_index: usize, l2_index: usize) -> usize {
+    fn l2<B, S>(pop: &FlatPopcount<B, S>, l1_index: usize, l2_index: usize) -> usize {
         let offset = 12 * (6 - l2_index);
         ((pop.l1_index[l1_index] >> offset) & L2_INDEX_MASK) as usize
     }
@@ -329,7 +1026,7 @@ mod test {
             bv.set(i, i % 2 == 0)
         }
 
-        let pop = FlatPopcount::new(&bv);
+        let pop = FlatPopcount::<_, LinearSearch>::new(&bv);
 
         assert_eq!(bv.len(), pop.len(), "length of rank ds not equal to length of bit vec");
         assert!(!pop.is_empty(), "rank ds empty despite not being empty");
@@ -356,7 +1053,7 @@ mod test {
             bv.set(i, i & 2 == 0)
         }
 
-        let pop = FlatPopcount::<()>::new(&bv);
+        let pop = FlatPopcount::<_, ()>::new(&bv);
 
         let mut ones = 0;
         for i in 0..bv.len() {
@@ -364,6 +1061,67 @@ mod test {
             assert_eq!(i - ones, pop.rank::<false>(i), "index {i}");
             ones += if bv.get(i) { 1 } else { 0 };
         }
+        assert_eq!(ones, pop.rank::<true>(bv.len()));
+
+        let empty = FlatPopcount::<_, ()>::new(BitVec::new(0));
+        assert_eq!(0, empty.rank::<true>(0));
+        assert_eq!(0, empty.rank::<false>(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rank_out_of_bounds_test() {
+        FlatPopcount::<_, ()>::new(BitVec::new(20)).rank::<true>(21);
+    }
+
+    #[test]
+    fn backing_test() {
+        let mut bv = BitVec::new(10000);
+        for i in 0..bv.len() {
+            bv.set(i, i % 3 == 0)
+        }
+        let expected = FlatPopcount::<_, BinarySearch>::new(&bv);
+
+        let check = |pop: &dyn Fn(usize) -> (usize, Option<usize>)| {
+            for i in (0..bv.len()).step_by(7) {
+                assert_eq!(
+                    (expected.rank::<true>(i), SelectSupport::<true>::select(&expected, i / 3)),
+                    pop(i),
+                    "index {i}"
+                );
+            }
+        };
+
+        let owned = FlatPopcount::<_, BinarySearch>::new(bv.clone());
+        check(&|i| (owned.rank::<true>(i), SelectSupport::<true>::select(&owned, i / 3)));
+        assert_eq!(bv, owned.into_inner());
+
+        let rc = FlatPopcount::<_, BinarySearch>::new(Rc::new(bv.clone()));
+        check(&|i| (rc.rank::<true>(i), SelectSupport::<true>::select(&rc, i / 3)));
+        assert_eq!(bv, *rc.into_inner());
+
+        let arc = FlatPopcount::<_, BinarySearch>::new(Arc::new(bv.clone()));
+        check(&|i| (arc.rank::<true>(i), SelectSupport::<true>::select(&arc, i / 3)));
+        assert_eq!(&bv, arc.backing().as_ref());
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut bv = BitVec::new(10000);
+        for i in 0..bv.len() {
+            bv.set(i, i % 3 == 0)
+        }
+
+        let pop = FlatPopcount::<_, ()>::new(&bv);
+        assert_eq!(3334, pop.number_of_ones());
+
+        let ones = pop.iter_ones();
+        assert_eq!(3334, ones.len());
+        assert!(ones.eq((0..bv.len()).step_by(3)));
+
+        let zeros = pop.iter_zeros();
+        assert_eq!(6666, zeros.len());
+        assert!(zeros.rev().eq((0..bv.len()).rev().filter(|i| i % 3 != 0)));
     }
 
     #[test]
@@ -374,9 +1132,9 @@ mod test {
             bv.set(i, i % 2 == 0)
         }
 
-        let pop = FlatPopcount::<BinarySearch>::new(&bv);
-        for i in 1..bv.len() / 2 {
-            assert_eq!(Some(2 * i), pop.select(i), "{i}th one should be at index {}", 2 * i);
+        let pop = FlatPopcount::<_, BinarySearch>::new(&bv);
+        for i in 0..bv.len() / 2 {
+            assert_eq!(Some(2 * i), SelectSupport::<true>::select(&pop, i), "{i}th one should be at index {}", 2 * i);
         }
     }
 
@@ -389,9 +1147,322 @@ mod test {
             bv.set(i, i % 2 == 0)
         }
 
-        let pop = FlatPopcount::<BinarySearch>::new(&bv);
+        let pop = FlatPopcount::<_, BinarySearch>::new(&bv);
+
+        assert_eq!(None, SelectSupport::<true>::select(&pop, 100000));
+        assert_eq!(None, SelectSupport::<true>::select(&pop, 25000));
+        assert_eq!(None, SelectSupport::<false>::select(&pop, 25000));
+    }
+
+    #[test]
+    fn select_zero_test() {
+        let mut bv = BitVec::new(50000);
+
+        for i in 0..bv.len() {
+            bv.set(i, i % 2 == 0)
+        }
+
+        let pop = FlatPopcount::<_, BinarySearch>::new(&bv);
+        for i in 0..bv.len() / 2 {
+            assert_eq!(
+                Some(2 * i + 1),
+                SelectSupport::<false>::select(&pop, i),
+                "{i}th zero should be at index {}",
+                2 * i + 1
+            );
+        }
+    }
+
+    fn select_matches_naive<Strat: SelectStrategy>() {
+        // Pattern with sparse and dense regions to get uneven l2 blocks
+        let mut bv = BitVec::new(70000);
+        for i in 0..bv.len() {
+            let dense = (i / 3000) % 2 == 0;
+            bv.set(i, if dense { i % 7 != 0 } else { i % 13 == 0 })
+        }
 
-        assert_eq!(None, pop.select(100000));
+        let pop = FlatPopcount::<_, Strat>::new(&bv);
+        let (mut ones, mut zeros) = (0, 0);
+        for i in 0..bv.len() {
+            if bv.get(i) {
+                assert_eq!(Some(i), SelectSupport::<true>::select(&pop, ones), "{ones}th one");
+                ones += 1;
+            } else {
+                assert_eq!(Some(i), SelectSupport::<false>::select(&pop, zeros), "{zeros}th zero");
+                zeros += 1;
+            }
+        }
+        assert_eq!(None, SelectSupport::<true>::select(&pop, ones));
+        assert_eq!(None, SelectSupport::<false>::select(&pop, zeros));
+    }
+
+    #[test]
+    fn select_linear_search_test() {
+        select_matches_naive::<LinearSearch>()
+    }
+
+    #[test]
+    fn select_binary_search_test() {
+        select_matches_naive::<BinarySearch>()
+    }
+
+    #[test]
+    fn select_runtime_simd_search_test() {
+        select_matches_naive::<RuntimeSimdSearch>()
     }
 
+    #[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "sse2",
+    target_feature = "ssse3",
+    target_feature = "sse4.1"
+    ))]
+    #[test]
+    fn select_simd_search_test() {
+        select_matches_naive::<crate::rank_select::flat_popcount::SimdSearch>()
+    }
+
+    fn rank_select_matches_naive<B: WordAccess, I: AsRef<[u128]>>(
+        pop: &FlatPopcount<B, LinearSearch, I>,
+        bit: impl Fn(usize) -> bool,
+    ) {
+        let (mut ones, mut zeros) = (0, 0);
+        for i in 0..pop.len() {
+            assert_eq!(ones, pop.rank::<true>(i), "rank of ones at {i}");
+            assert_eq!(zeros, pop.rank::<false>(i), "rank of zeros at {i}");
+            if bit(i) {
+                assert_eq!(Some(i), SelectSupport::<true>::select(pop, ones), "{ones}th one");
+                ones += 1;
+            } else {
+                assert_eq!(Some(i), SelectSupport::<false>::select(pop, zeros), "{zeros}th zero");
+                zeros += 1;
+            }
+        }
+        assert_eq!(ones, pop.rank::<true>(pop.len()));
+        assert_eq!(ones, pop.iter_ones().len());
+        assert_eq!(None, SelectSupport::<true>::select(pop, ones));
+        assert_eq!(None, SelectSupport::<false>::select(pop, zeros));
+    }
+
+    #[test]
+    fn word_access_test() {
+        let mut bv = BitVec::new(20000);
+        for i in 0..bv.len() {
+            bv.set(i, (i * i + i / 5) % 7 < 2);
+        }
+
+        // Unaligned slice
+        let slice = bv.slice(37..9000);
+        let pop = FlatPopcount::new(&slice);
+        rank_select_matches_naive(&pop, |i| slice.get(i));
+
+        // Raw words, which also cover the unused bits of the last word
+        let raw = bv.raw();
+        let bit = |i: usize| (raw[i / 64] >> (i % 64)) & 1 == 1;
+        let pop = FlatPopcount::new(raw);
+        assert_eq!(raw.len() * 64, pop.len());
+        rank_select_matches_naive(&pop, bit);
+
+        let words = raw.to_vec();
+        let pop = FlatPopcount::new(words);
+        rank_select_matches_naive(&pop, bit);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn serialize_test() {
+        let mut bv = BitVec::new(70000);
+        for i in 0..bv.len() {
+            bv.set(i, (i / 3000) % 2 == 0 || i % 13 == 0);
+        }
+        let pop = FlatPopcount::<_, LinearSearch>::new(&bv);
+        let mut buf = Vec::new();
+        pop.write_to(&mut buf).unwrap();
+
+        let read = FlatPopcount::<_, LinearSearch>::read_from(&bv, &mut buf.as_slice()).unwrap();
+        assert_eq!(pop.l1_index, read.l1_index);
+        assert_eq!(pop.number_of_ones(), read.number_of_ones());
+        assert_eq!(pop.sampled_ones.raw_data(), read.sampled_ones.raw_data());
+        assert_eq!(pop.sampled_zeros.raw_data(), read.sampled_zeros.raw_data());
+        rank_select_matches_naive(&read, |i| bv.get(i));
+
+        let empty = BitVec::new(0);
+        let mut buf = Vec::new();
+        FlatPopcount::<_, LinearSearch>::new(&empty).write_to(&mut buf).unwrap();
+        let read = FlatPopcount::<_, LinearSearch>::read_from(&empty, &mut buf.as_slice()).unwrap();
+        assert_eq!(None, SelectSupport::<true>::select(&read, 0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deserialize_invalid_test() {
+        let bv: BitVec = (0..20000).map(|i| i % 3 == 0).collect();
+        let pop = FlatPopcount::<_, LinearSearch>::new(&bv);
+        let mut buf = Vec::new();
+        pop.write_to(&mut buf).unwrap();
+        let read = |bv: &BitVec, buf: &[u8]| {
+            FlatPopcount::<_, LinearSearch>::read_from(bv, &mut &buf[..]).map(|_| ())
+        };
+
+        assert!(matches!(read(&bv, &buf[..buf.len() - 1]), Err(SerializeError::Io(_))));
+
+        // Different length
+        let mut other = bv.clone();
+        other.push(false);
+        assert!(matches!(read(&other, &buf), Err(SerializeError::Invalid(_))));
+
+        // The first L2 entry of the first L1 entry now exceeds the size of an L2 block
+        let mut corrupted = buf.clone();
+        corrupted[42] |= 0x0f;
+        assert!(matches!(read(&bv, &corrupted), Err(SerializeError::Invalid(_))));
+
+        // The first L1 entry is not zero
+        let mut corrupted = buf.clone();
+        corrupted[47] = 1;
+        assert!(matches!(read(&bv, &corrupted), Err(SerializeError::Invalid(_))));
+
+        // The ones in the last L1 entry do not add up to the number of ones
+        let mut corrupted = buf.clone();
+        corrupted[16] ^= 1;
+        assert!(matches!(read(&bv, &corrupted), Err(SerializeError::Invalid(_))));
+
+        // The first sample of ones now points past the last L1 block
+        let samples_start = 32 + 16 * pop.l1_index.len();
+        let mut corrupted = buf.clone();
+        corrupted[samples_start + 24] = 0xff;
+        assert!(matches!(read(&bv, &corrupted), Err(SerializeError::Invalid(_))));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn verify_test() {
+        let bv: BitVec = (0..20000).map(|i| i % 3 == 0).collect();
+        let pop = FlatPopcount::<_, LinearSearch>::new(&bv);
+        assert!(pop.verify());
+        let mut buf = Vec::new();
+        pop.write_to(&mut buf).unwrap();
+
+        // An index for different bits of the same length is consistent, so only verifying
+        // detects it
+        let mut other = bv.clone();
+        other.flip(5000);
+        let read = FlatPopcount::<_, LinearSearch>::read_from(&other, &mut buf.as_slice()).unwrap();
+        assert!(!read.verify());
+
+        // Queries must stay in bounds even though the index does not match
+        let zeros = BitVec::new(bv.len());
+        let read = FlatPopcount::<_, LinearSearch>::read_from(&zeros, &mut buf.as_slice()).unwrap();
+        assert!(!read.verify());
+        for rank in 0..read.number_of_ones() {
+            assert_eq!(None, SelectSupport::<true>::select(&read, rank));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_bytes_test() {
+        let mut bv = BitVec::new(70000);
+        for i in 0..bv.len() {
+            bv.set(i, (i / 3000) % 2 == 0 || i % 13 == 0);
+        }
+        let mut bv_buf = Vec::new();
+        bv.write_to(&mut bv_buf).unwrap();
+        let mut index_buf = Vec::new();
+        FlatPopcount::<_, LinearSearch>::new(&bv).write_to(&mut index_buf).unwrap();
+        let (bv_buf, index_buf) = (AlignedBuf::new(&bv_buf), AlignedBuf::new(&index_buf));
+
+        let bits = BitSlice::from_bytes(bv_buf.bytes()).unwrap();
+        let view = FlatPopcountView::from_bytes(bits, index_buf.bytes()).unwrap();
+        rank_select_matches_naive(&view, |i| bv.get(i));
+
+        let bits = BitSlice::from_bytes(bv_buf.bytes()).unwrap();
+        let view: FlatPopcountView =
+            unsafe { FlatPopcountView::from_bytes_unchecked(bits, index_buf.bytes()).unwrap() };
+        assert_eq!(bv.count_ones(), view.rank::<true>(bv.len()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_bytes_invalid_test() {
+        let bv: BitVec = (0..20000).map(|i| i % 3 == 0).collect();
+        let mut buf = Vec::new();
+        FlatPopcount::<_, LinearSearch>::new(&bv).write_to(&mut buf).unwrap();
+        let from_bytes = |bv: &BitVec, bytes: &[u8]| {
+            FlatPopcount::<_, LinearSearch, &[u128]>::from_bytes(bv, bytes).map(|_| ())
+        };
+
+        // The L1 index is not aligned to 16 bytes
+        let mut shifted = vec![0; 8];
+        shifted.extend_from_slice(&buf);
+        let shifted = AlignedBuf::new(&shifted);
+        let res = from_bytes(&bv, &shifted.bytes()[8..]);
+        assert!(matches!(res, Err(SerializeError::Misaligned(16))));
+
+        // Trailing bytes
+        let mut padded = buf.clone();
+        padded.push(0);
+        let res = from_bytes(&bv, AlignedBuf::new(&padded).bytes());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // Missing bytes
+        let res = from_bytes(&bv, &AlignedBuf::new(&buf).bytes()[..buf.len() - 1]);
+        assert!(res.is_err());
+        let res = from_bytes(&bv, &AlignedBuf::new(&buf).bytes()[..100]);
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // Inconsistent L1/L2 index
+        let mut corrupted = buf.clone();
+        corrupted[42] |= 0x0f;
+        let res = from_bytes(&bv, AlignedBuf::new(&corrupted).bytes());
+        assert!(matches!(res, Err(SerializeError::Invalid(_))));
+
+        // Different bits are only detected by verifying
+        let mut other = bv.clone();
+        other.flip(5000);
+        let buf = AlignedBuf::new(&buf);
+        let view = FlatPopcount::<_, LinearSearch, &[u128]>::from_bytes(&other, buf.bytes());
+        assert!(!view.unwrap().verify());
+    }
+
+    #[test]
+    fn modify_test() {
+        // Just above 6 * 8192 ones, so that the number of samples changes
+        let len = 66001;
+        let mut bits = (0..len).map(|i| i % 4 != 0).collect::<Vec<_>>();
+        let bv: BitVec = bits.iter().copied().collect();
+        let mut pop = FlatPopcount::<_, LinearSearch>::new(bv);
+
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for round in 0..5000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let index = (state % len as u64) as usize;
+            // Mostly clear bits first and only set them afterwards
+            let value = state >> 60 == 0 || round >= 1500;
+            if round % 2 == 0 || round >= 1500 {
+                pop.set_bit(index, value);
+                bits[index] = value;
+            } else {
+                pop.flip_bit(index);
+                bits[index] = !bits[index];
+            }
+
+            if round % 500 == 0 {
+                let fresh = FlatPopcount::<_, LinearSearch>::new(pop.backing().clone());
+                assert_eq!(fresh.l1_index, pop.l1_index, "L1 index after round {round}");
+                assert_eq!(fresh.number_of_ones, pop.number_of_ones);
+                assert!(fresh.sampled_ones.iter().eq(pop.sampled_ones.iter()));
+                assert!(fresh.sampled_zeros.iter().eq(pop.sampled_zeros.iter()));
+            }
+        }
+        rank_select_matches_naive(&pop, |i| bits[i]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn modify_out_of_bounds_test() {
+        let mut pop = FlatPopcount::<_, LinearSearch>::new(BitVec::new(100));
+        pop.flip_bit(100);
+    }
 }
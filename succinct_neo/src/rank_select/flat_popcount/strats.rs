@@ -1,4 +1,4 @@
-use super::L2_INDEX_MASK;
+use super::{L2_BLOCK_SIZE_EXP, L2_INDEX_MASK};
 
 #[cfg(all(
 target_arch = "x86_64",
@@ -9,18 +9,52 @@ target_feature = "sse4.1"
 pub use simd::SimdSearch;
 
 pub trait SelectStrategy {
+    /// Finds the L2 block inside of an L1 block which contains the one with the given rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The L1 index entry containing the packed L2 entries.
+    /// * `rank` - The rank of the one to find relative to the start of the L1 block.
+    ///
+    /// returns: The index of the L2 block and the number of ones in the L1 block before it.
     fn find_l2(entry: u128, rank: usize) -> (usize, usize);
+
+    /// Finds the L2 block inside of an L1 block which contains the zero with the given rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The L1 index entry containing the packed L2 entries.
+    /// * `rank` - The rank of the zero to find relative to the start of the L1 block.
+    ///
+    /// returns: The index of the L2 block and the number of zeros in the L1 block before it.
+    fn find_l2_zeros(entry: u128, rank: usize) -> (usize, usize);
+}
+
+/// Gets the number of ones in the L1 block up to and including the given L2 block.
+#[inline]
+fn l2_ones(entry: u128, l2_index: usize) -> usize {
+    ((entry >> (72 - 12 * l2_index)) & L2_INDEX_MASK) as usize
+}
+
+/// Gets the number of zeros in the L1 block up to and including the given L2 block.
+///
+/// The unused L2 entries of the last L1 block are filled with ones, which would result in a
+/// negative number of zeros. Wrapping makes these entries larger than any valid rank instead.
+#[inline]
+fn l2_zeros(entry: u128, l2_index: usize) -> usize {
+    ((l2_index + 1) << L2_BLOCK_SIZE_EXP).wrapping_sub(l2_ones(entry, l2_index))
 }
 
 /// A search strategy using a simple linear search to locate the correct l2 block.
 pub struct LinearSearch;
 
-impl SelectStrategy for LinearSearch {
-    fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
+impl LinearSearch {
+    #[inline]
+    fn search(rank: usize, l2: impl Fn(usize) -> usize) -> (usize, usize) {
         let mut prev = 0;
 
         for i in 0..7 {
-            let l2_entry = ((entry >> (72 - 12 * i)) & L2_INDEX_MASK) as usize;
+            let l2_entry = l2(i);
             if rank < l2_entry {
                 return (i, prev);
             }
@@ -31,30 +65,35 @@ impl SelectStrategy for LinearSearch {
     }
 }
 
+impl SelectStrategy for LinearSearch {
+    fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
+        Self::search(rank, |i| l2_ones(entry, i))
+    }
+
+    fn find_l2_zeros(entry: u128, rank: usize) -> (usize, usize) {
+        Self::search(rank, |i| l2_zeros(entry, i))
+    }
+}
+
 /// A search strategy using a uniform binary search to locate the correct l2 block.
 /// This always requires 3 search steps.
 pub struct BinarySearch;
 
-impl SelectStrategy for BinarySearch {
-    fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
-        macro_rules! l2 {
-            ($l2_index:literal) => {
-                ((entry >> (72 - 12 * $l2_index)) & L2_INDEX_MASK) as usize
-            };
-        }
-
-        let l2_3 = l2!(3);
+impl BinarySearch {
+    #[inline]
+    fn search(rank: usize, l2: impl Fn(usize) -> usize) -> (usize, usize) {
+        let l2_3 = l2(3);
         if rank < l2_3 {
-            let l2_1 = l2!(1);
+            let l2_1 = l2(1);
             if rank < l2_1 {
-                let l2_0 = l2!(0);
+                let l2_0 = l2(0);
                 if rank < l2_0 {
                     (0, 0)
                 } else {
                     (1, l2_0)
                 }
             } else {
-                let l2_2 = l2!(2);
+                let l2_2 = l2(2);
                 if rank < l2_2 {
                     (2, l2_1)
                 } else {
@@ -62,16 +101,16 @@ impl SelectStrategy for BinarySearch {
                 }
             }
         } else {
-            let l2_5 = l2!(5);
+            let l2_5 = l2(5);
             if rank < l2_5 {
-                let l2_4 = l2!(4);
+                let l2_4 = l2(4);
                 if rank < l2_4 {
                     (4, l2_3)
                 } else {
                     (5, l2_4)
                 }
             } else {
-                let l2_6 = l2!(6);
+                let l2_6 = l2(6);
                 if rank < l2_6 {
                     (6, l2_5)
                 } else {
@@ -82,63 +121,223 @@ impl SelectStrategy for BinarySearch {
     }
 }
 
-#[cfg(all(
-target_arch = "x86_64",
-target_feature = "sse2",
-target_feature = "ssse3",
-target_feature = "sse4.1"
-))]
+impl SelectStrategy for BinarySearch {
+    fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
+        Self::search(rank, |i| l2_ones(entry, i))
+    }
+
+    fn find_l2_zeros(entry: u128, rank: usize) -> (usize, usize) {
+        Self::search(rank, |i| l2_zeros(entry, i))
+    }
+}
+
+/// A search strategy which detects at runtime whether the CPU supports the SSE4.1 instructions
+/// used by `SimdSearch` and uses them if possible. Otherwise, it falls back to [`BinarySearch`].
+///
+/// Unlike `SimdSearch`, this is available regardless of the target features enabled at compile
+/// time, so portable binaries can still make use of SIMD instructions. If AVX2 is available, the
+/// same search is compiled with VEX-encoded instructions.
+///
+/// Runtime detection requires the `std` feature. Without it, only the target features enabled at
+/// compile time are used.
+pub struct RuntimeSimdSearch;
+
+impl SelectStrategy for RuntimeSimdSearch {
+    #[inline]
+    fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            // SAFETY: The required target features are checked right before
+            if x86_feature_detected!("avx2") {
+                return unsafe { simd::find_l2_avx2(entry, rank) };
+            }
+            if simd::sse41_detected() {
+                return unsafe { simd::find_l2_sse41(entry, rank) };
+            }
+        }
+        BinarySearch::find_l2(entry, rank)
+    }
+
+    #[inline]
+    fn find_l2_zeros(entry: u128, rank: usize) -> (usize, usize) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            // SAFETY: The required target features are checked right before
+            if x86_feature_detected!("avx2") {
+                return unsafe { simd::find_l2_zeros_avx2(entry, rank) };
+            }
+            if simd::sse41_detected() {
+                return unsafe { simd::find_l2_zeros_sse41(entry, rank) };
+            }
+        }
+        BinarySearch::find_l2_zeros(entry, rank)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
 mod simd {
-    use super::SelectStrategy;
-    use std::arch::x86_64::*;
+    use core::arch::x86_64::*;
     use crate::rank_select::flat_popcount::L2_INDEX_MASK;
 
+    /// A search strategy using SSE4.1 instructions to compare all L2 entries at once.
+    ///
+    /// This is only available if the required target features are enabled at compile time. See
+    /// [`RuntimeSimdSearch`](super::RuntimeSimdSearch) for a strategy which detects them at
+    /// runtime instead.
+    #[cfg(all(
+    target_feature = "sse2",
+    target_feature = "ssse3",
+    target_feature = "sse4.1"
+    ))]
     pub struct SimdSearch;
 
-    impl SelectStrategy for SimdSearch {
-        fn find_l2(mut entry: u128, rank: usize) -> (usize, usize) {
-            // We zero the L1 Index data in the entry
-            unsafe { *(&mut entry as *mut u128 as *mut u64).offset(1) &= (1 << 20) - 1; }
-            let rank = rank as i16;
-            let l2_index = unsafe {
-                // Put the values into a wide 128 bit register
-                let values = _mm_loadu_si128(&entry as *const u128 as *const __m128i);
-                // Don't even ask
-                let shuffle_mask =
-                    _mm_set_epi8(10, 9, 8, 7, 7, 6, 5, 4, 4, 3, 2, 1, 1, 0, -1, -1);
-                let values = _mm_shuffle_epi8(values, shuffle_mask);
-
-                // Shift values by 4 bits to the right
-                // This is to align the values of odd indices which still need alignment to the
-                // byte borders
-                let type_2 = _mm_srli_epi16::<4>(values);
-
-                let values = _mm_blend_epi16::<0b0101_0101>(values, type_2);
-
-                // We mask those elements to get rid of the junk we shifted in
-                let mask_12_bits = _mm_set1_epi16(0b1111_1111_1111);
-                let values = _mm_and_si128(values, mask_12_bits);
-
-                let ranks = _mm_set1_epi16(rank);
-
-                // we get a 128 bit word which contains a 1 as the MSB in each 16 bit block where
-                // the l2 index value is greater than the rank we want.
-                let result_spread = _mm_cmpgt_epi16(values, ranks);
-
-                // Collect them into a normal integer. Since the last two bytes are empty,
-                // we fill the corresponding bits with 1, so our calculations still work when counting leading 0s
-                let res = _mm_movemask_epi8(result_spread) | 0b11;
-
-                (res.leading_zeros() - 16) >> 1
-            } as usize;
-            (l2_index, ((entry >> (84 - 12 * l2_index)) & L2_INDEX_MASK) as usize)
+    #[cfg(all(
+    target_feature = "sse2",
+    target_feature = "ssse3",
+    target_feature = "sse4.1"
+    ))]
+    impl super::SelectStrategy for SimdSearch {
+        #[inline]
+        fn find_l2(entry: u128, rank: usize) -> (usize, usize) {
+            // SAFETY: The target features are enabled at compile time
+            unsafe { find_l2_sse41(entry, rank) }
+        }
+
+        #[inline]
+        fn find_l2_zeros(entry: u128, rank: usize) -> (usize, usize) {
+            // SAFETY: The target features are enabled at compile time
+            unsafe { find_l2_zeros_sse41(entry, rank) }
         }
     }
+
+    /// Returns `true` if the CPU supports all instructions used by the SSE4.1 search.
+    #[inline]
+    pub(super) fn sse41_detected() -> bool {
+        x86_feature_detected!("sse2", "ssse3", "sse4.1")
+    }
+
+    /// Zeroes the L1 index data in the entry, so only the L2 entries remain.
+    #[inline]
+    fn clear_l1(entry: u128) -> u128 {
+        entry & ((1 << 84) - 1)
+    }
+
+    /// Unpacks the L2 entries into 16 bit lanes. The entry for L2 block `i` is put into lane
+    /// `7 - i` while the lowest lane is zero.
+    ///
+    /// # Safety
+    ///
+    /// The L1 index data in the entry must be zeroed and the CPU must support SSE4.1.
+    #[inline]
+    #[target_feature(enable = "sse2,ssse3,sse4.1")]
+    unsafe fn unpack(entry: &u128) -> __m128i {
+        // Put the values into a wide 128 bit register
+        let values = _mm_loadu_si128(entry as *const u128 as *const __m128i);
+        // Don't even ask
+        let shuffle_mask =
+            _mm_set_epi8(10, 9, 8, 7, 7, 6, 5, 4, 4, 3, 2, 1, 1, 0, -1, -1);
+        let values = _mm_shuffle_epi8(values, shuffle_mask);
+
+        // Shift values by 4 bits to the right
+        // This is to align the values of odd indices which still need alignment to the
+        // byte borders
+        let type_2 = _mm_srli_epi16::<4>(values);
+
+        let values = _mm_blend_epi16::<0b0101_0101>(values, type_2);
+
+        // We mask those elements to get rid of the junk we shifted in
+        let mask_12_bits = _mm_set1_epi16(0b1111_1111_1111);
+        _mm_and_si128(values, mask_12_bits)
+    }
+
+    /// Calculates the number of lanes (from the highest lane downwards) whose comparison
+    /// result is not set.
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn count_leading_lanes(result_spread: __m128i) -> usize {
+        // Collect them into a normal integer. Since the last two bytes are empty,
+        // we fill the corresponding bits with 1, so our calculations still work when counting leading 0s
+        let res = _mm_movemask_epi8(result_spread) | 0b11;
+
+        ((res.leading_zeros() - 16) >> 1) as usize
+    }
+
+    /// Finds the L2 block containing the one with the given rank using SSE4.1 instructions.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE4.1.
+    #[inline]
+    #[target_feature(enable = "sse2,ssse3,sse4.1")]
+    pub(super) unsafe fn find_l2_sse41(entry: u128, rank: usize) -> (usize, usize) {
+        let entry = clear_l1(entry);
+        let rank = rank as i16;
+        let values = unpack(&entry);
+
+        let ranks = _mm_set1_epi16(rank);
+
+        // we get a 128 bit word which contains a 1 as the MSB in each 16 bit block where
+        // the l2 index value is greater than the rank we want.
+        let result_spread = _mm_cmpgt_epi16(values, ranks);
+
+        let l2_index = count_leading_lanes(result_spread);
+        (l2_index, ((entry >> (84 - 12 * l2_index)) & L2_INDEX_MASK) as usize)
+    }
+
+    /// Finds the L2 block containing the zero with the given rank using SSE4.1 instructions.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE4.1.
+    #[inline]
+    #[target_feature(enable = "sse2,ssse3,sse4.1")]
+    pub(super) unsafe fn find_l2_zeros_sse41(entry: u128, rank: usize) -> (usize, usize) {
+        let entry = clear_l1(entry);
+        let rank = rank as i16;
+        let values = unpack(&entry);
+
+        // The number of zeros is the number of bits covered up to each l2 block minus the
+        // number of ones in them
+        let covered_bits = _mm_set_epi16(512, 1024, 1536, 2048, 2560, 3072, 3584, 0);
+        let zeros = _mm_sub_epi16(covered_bits, values);
+
+        // The unused l2 entries of the last l1 block result in negative values. We compare
+        // unsigned by flipping the sign bits, so these are greater than any rank.
+        let sign_bits = _mm_set1_epi16(i16::MIN);
+        let zeros = _mm_xor_si128(zeros, sign_bits);
+        let ranks = _mm_xor_si128(_mm_set1_epi16(rank), sign_bits);
+
+        let result_spread = _mm_cmpgt_epi16(zeros, ranks);
+
+        let l2_index = count_leading_lanes(result_spread);
+        let ones = ((entry >> (84 - 12 * l2_index)) & L2_INDEX_MASK) as usize;
+        (l2_index, (l2_index << 9).wrapping_sub(ones))
+    }
+
+    /// The same as [`find_l2_sse41`], but compiled with VEX-encoded instructions.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn find_l2_avx2(entry: u128, rank: usize) -> (usize, usize) {
+        find_l2_sse41(entry, rank)
+    }
+
+    /// The same as [`find_l2_zeros_sse41`], but compiled with VEX-encoded instructions.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn find_l2_zeros_avx2(entry: u128, rank: usize) -> (usize, usize) {
+        find_l2_zeros_sse41(entry, rank)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{LinearSearch, BinarySearch, SelectStrategy};
+    use super::{LinearSearch, BinarySearch, RuntimeSimdSearch, SelectStrategy};
 
     macro_rules! strat_tests {
         {$strat:ty, $test_name:ident} => {
@@ -157,6 +356,16 @@ mod test {
                 fn [<$test_name _equal_test>]() {
                     strat_test_equal_ranks::<$strat>()
                 }
+
+                #[test]
+                fn [<$test_name _zeros_test>]() {
+                    strat_test_zeros::<$strat>()
+                }
+
+                #[test]
+                fn [<$test_name _zeros_padding_test>]() {
+                    strat_test_zeros_padding::<$strat>()
+                }
             }
         };
         {$strat:ty, $test_name:ident, $($next_strat:ty, $next_test_name:ident),+} => {
@@ -167,7 +376,8 @@ mod test {
 
     strat_tests! {
         LinearSearch, linear_search,
-        BinarySearch, binary_search
+        BinarySearch, binary_search,
+        RuntimeSimdSearch, runtime_simd_search
     }
 
     #[cfg(all(
@@ -277,4 +487,49 @@ mod test {
             assert_eq!(expected, Strat::find_l2(entry, i), "index {i}");
         }
     }
+
+    /// Packs cumulative l2 popcounts into an l1 index entry
+    fn pack(l1: u128, l2: [u128; 7]) -> u128 {
+        let mut entry = l1;
+        for v in l2 {
+            entry <<= 12;
+            entry |= v;
+        }
+        entry
+    }
+
+    #[inline]
+    #[rustfmt::skip]
+    fn strat_test_zeros<Strat: SelectStrategy>() {
+        // Add random data to the l1 field to ensure this doesn't mess with anything
+        let entry = pack(123456789, [100, 300, 700, 900, 1200, 1500, 2000]);
+
+        // Cumulative zeros are 412, 724, 836, 1148, 1360, 1572, 1584
+        for i in 0..4096usize {
+            let expected = match i {
+                _ if i < 412 => (0, 0),
+                _ if i < 724 => (1, 412),
+                _ if i < 836 => (2, 724),
+                _ if i < 1148 => (3, 836),
+                _ if i < 1360 => (4, 1148),
+                _ if i < 1572 => (5, 1360),
+                _ if i < 1584 => (6, 1572),
+                _ => (7, 1584)
+            };
+            assert_eq!(expected, Strat::find_l2_zeros(entry, i), "index {i}");
+        }
+    }
+
+    #[inline]
+    #[rustfmt::skip]
+    fn strat_test_zeros_padding<Strat: SelectStrategy>() {
+        // The last l1 block has its unused l2 entries filled with ones
+        let entry = pack(123456789, [100, 300, 4095, 4095, 4095, 4095, 4095]);
+
+        // Cumulative zeros are 412 and 724 followed by unused blocks
+        for i in 0..724usize {
+            let expected = if i < 412 { (0, 0) } else { (1, 412) };
+            assert_eq!(expected, Strat::find_l2_zeros(entry, i), "index {i}");
+        }
+    }
 }
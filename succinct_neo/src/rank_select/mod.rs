@@ -1,5 +1,8 @@
 pub mod flat_popcount;
+pub mod rrr;
 mod traits;
 
-pub use traits::RankSupport;
+pub use traits::{RankSupport, SelectSupport};
 pub use flat_popcount::FlatPopcount;
+pub use flat_popcount::FlatPopcountView;
+pub use rrr::Rrr;
@@ -0,0 +1,471 @@
+use alloc::vec::Vec;
+
+use crate::bit_vec::{BitGet, BitModify, BitVec, Word};
+use crate::int_vec::{IntAccess, IntVec};
+
+use super::traits::{RankSupport, SelectSupport};
+
+/// The number of blocks per superblock, for which the rank and the position of the offsets are
+/// sampled.
+const SUPERBLOCK_BLOCKS: usize = 32;
+
+/// The largest supported block size.
+const MAX_BLOCK_SIZE: usize = 63;
+
+/// `BINOMIALS[n][k]` is the binomial coefficient *n choose k*.
+static BINOMIALS: [[Word; MAX_BLOCK_SIZE + 1]; MAX_BLOCK_SIZE + 1] = binomials();
+
+const fn binomials() -> [[Word; MAX_BLOCK_SIZE + 1]; MAX_BLOCK_SIZE + 1] {
+    let mut table = [[0; MAX_BLOCK_SIZE + 1]; MAX_BLOCK_SIZE + 1];
+    let mut n = 0;
+    while n <= MAX_BLOCK_SIZE {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+/// The number of bits needed to store any value less than or equal to `max`. This is at least 1.
+#[inline]
+fn bits_for(max: usize) -> usize {
+    (usize::BITS - max.leading_zeros()).max(1) as usize
+}
+
+/// A compressed bit vector supporting rank and select queries, as described by Raman, Raman and
+/// Rao in *Succinct indexable dictionaries with applications to encoding k-ary trees, prefix sums
+/// and multisets*.
+///
+/// The bits are split into blocks of `BLOCK_SIZE` bits. Each block is stored as its class (the
+/// number of ones in it) and its offset, which identifies the block among all blocks of the same
+/// class. Since the offset needs only $\lceil \log_2 \binom{b}{k} \rceil$ bits, blocks with few or
+/// many ones take up less space. `BLOCK_SIZE` can be at most 63. Larger blocks compress better,
+/// but make queries slower, since a block needs to be decoded bit by bit.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::{
+///     bit_vec::{BitGet, BitVec},
+///     rank_select::{RankSupport, Rrr, SelectSupport}
+/// };
+///
+/// let bv: BitVec = (0..1000).map(|i| i % 100 == 0).collect();
+/// let rrr = Rrr::<31>::new(&bv);
+///
+/// assert!(rrr.get_bit(300));
+/// assert_eq!(4, rrr.rank::<true>(301));
+/// assert_eq!(Some(500), SelectSupport::<true>::select(&rrr, 5));
+/// assert_eq!(Some(2), SelectSupport::<false>::select(&rrr, 1));
+/// ```
+pub struct Rrr<const BLOCK_SIZE: usize = 15> {
+    /// The number of ones in each block
+    classes: IntVec,
+    /// The offsets of all blocks, each stored with the minimal width for its class
+    offsets: BitVec,
+    /// The number of ones before each superblock
+    sampled_ranks: IntVec,
+    /// The position of the offset of the first block of each superblock
+    sampled_offsets: IntVec,
+    number_of_ones: usize,
+    len: usize,
+}
+
+impl<const BLOCK_SIZE: usize> Rrr<BLOCK_SIZE> {
+    /// Compresses a bit vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bv` - The bit vector to compress.
+    pub fn new(bv: &BitVec) -> Self {
+        Self::from_bits(&**bv, bv.len())
+    }
+
+    /// Compresses the bits from any data structure supporting [`BitGet`].
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The data structure to read the bits from.
+    /// * `len` - The number of bits to read from `source`, starting at index 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use succinct_neo::{
+    ///     bit_vec::BitVec,
+    ///     rank_select::{RankSupport, Rrr}
+    /// };
+    ///
+    /// let bv: BitVec = (0..1000).map(|i| i % 3 == 0).collect();
+    /// let slice = bv.slice(1..100);
+    /// let rrr = Rrr::<15>::from_bits(&slice, slice.len());
+    ///
+    /// assert_eq!(33, rrr.rank::<true>(99));
+    /// ```
+    pub fn from_bits(source: &impl BitGet, len: usize) -> Self {
+        const {
+            assert!(
+                0 < BLOCK_SIZE && BLOCK_SIZE <= MAX_BLOCK_SIZE,
+                "block size must be between 1 and 63"
+            )
+        }
+        let num_blocks = len.div_ceil(BLOCK_SIZE);
+        let mut classes = IntVec::with_capacity(bits_for(BLOCK_SIZE), num_blocks);
+        let mut offsets = BitVec::new(0);
+        let mut ranks = Vec::with_capacity(num_blocks / SUPERBLOCK_BLOCKS + 1);
+        let mut offset_positions = Vec::with_capacity(num_blocks / SUPERBLOCK_BLOCKS + 1);
+
+        let mut number_of_ones = 0;
+        for block in 0..num_blocks {
+            if block % SUPERBLOCK_BLOCKS == 0 {
+                ranks.push(number_of_ones);
+                offset_positions.push(offsets.len());
+            }
+            let start = block * BLOCK_SIZE;
+            let bits = source.get_bits(start, BLOCK_SIZE.min(len - start));
+            let class = bits.count_ones() as usize;
+            classes.push(class);
+            number_of_ones += class;
+
+            let width = Self::offset_width(class);
+            if width > 0 {
+                let position = offsets.len();
+                offsets.resize(position + width, false);
+                offsets.set_bits(position, width, Self::encode(bits, class));
+            }
+        }
+        // Sentinel sample for queries at the end of the bit vector
+        ranks.push(number_of_ones);
+        offset_positions.push(offsets.len());
+
+        let mut sampled_ranks = IntVec::with_capacity(bits_for(number_of_ones), ranks.len());
+        ranks.into_iter().for_each(|rank| sampled_ranks.push(rank));
+        let mut sampled_offsets =
+            IntVec::with_capacity(bits_for(offsets.len()), offset_positions.len());
+        offset_positions
+            .into_iter()
+            .for_each(|position| sampled_offsets.push(position));
+
+        Self {
+            classes,
+            offsets,
+            sampled_ranks,
+            sampled_offsets,
+            number_of_ones,
+            len,
+        }
+    }
+
+    /// Gets the number of bits in this bit vector.
+    ///
+    /// This is *not* the number of ones in the bit vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this bit vector has length 0.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the number of ones in this bit vector.
+    #[inline]
+    pub fn number_of_ones(&self) -> usize {
+        self.number_of_ones
+    }
+
+    /// The number of bits needed to store the offset of a block of the given class.
+    #[inline]
+    fn offset_width(class: usize) -> usize {
+        match BINOMIALS[BLOCK_SIZE][class] {
+            1 => 0,
+            count => (Word::BITS - (count - 1).leading_zeros()) as usize,
+        }
+    }
+
+    /// Calculates the offset of a block, which is its index among all blocks of the same class,
+    /// using the combinatorial number system.
+    fn encode(mut bits: Word, class: usize) -> Word {
+        let mut offset = 0;
+        let mut remaining = class;
+        while bits != 0 {
+            let position = (Word::BITS - 1 - bits.leading_zeros()) as usize;
+            offset += BINOMIALS[position][remaining];
+            remaining -= 1;
+            bits &= !(1 << position);
+        }
+        offset
+    }
+
+    /// Restores the bits of a block from its class and offset.
+    fn decode(mut offset: Word, class: usize) -> Word {
+        let mut bits = 0;
+        let mut remaining = class;
+        for position in (0..BLOCK_SIZE).rev() {
+            if remaining == 0 {
+                break;
+            }
+            let count = BINOMIALS[position][remaining];
+            if offset >= count {
+                bits |= 1 << position;
+                offset -= count;
+                remaining -= 1;
+            }
+        }
+        bits
+    }
+
+    /// Decodes a block given the position of its offset.
+    ///
+    /// returns: The bits of the block and its class.
+    ///
+    /// # Safety
+    ///
+    /// The block and offset position must be in bounds.
+    #[inline]
+    unsafe fn block_at(&self, block: usize, offset_position: usize) -> (Word, usize) {
+        let class = self.classes.get_unchecked(block);
+        let width = Self::offset_width(class);
+        let offset = if width == 0 {
+            0
+        } else {
+            self.offsets.get_bits_unchecked(offset_position, width)
+        };
+        (Self::decode(offset, class), class)
+    }
+
+    /// Finds the position of the offset of a block and the number of ones before it.
+    ///
+    /// # Safety
+    ///
+    /// The block must be in bounds. It may be equal to the number of blocks.
+    #[inline]
+    unsafe fn locate(&self, block: usize) -> (usize, usize) {
+        let superblock = block / SUPERBLOCK_BLOCKS;
+        let mut ones = self.sampled_ranks.get_unchecked(superblock);
+        let mut position = self.sampled_offsets.get_unchecked(superblock);
+        for i in superblock * SUPERBLOCK_BLOCKS..block {
+            let class = self.classes.get_unchecked(i);
+            ones += class;
+            position += Self::offset_width(class);
+        }
+        (position, ones)
+    }
+
+    /// Finds the position of the one or zero with the given rank.
+    ///
+    /// # Generic Arguments
+    ///
+    /// * `TARGET` - `true` if a one should be searched, `false` if a zero should be searched.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank` - The rank of the zero/one to find. The first zero/one has rank `0`.
+    fn select_impl<const TARGET: bool>(&self, mut rank: usize) -> Option<usize> {
+        let total = if TARGET {
+            self.number_of_ones
+        } else {
+            self.len - self.number_of_ones
+        };
+        if rank >= total {
+            return None;
+        }
+        // The number of ones/zeroes before the given superblock
+        let sampled_rank = |superblock: usize| {
+            let ones = unsafe { self.sampled_ranks.get_unchecked(superblock) };
+            if TARGET {
+                ones
+            } else {
+                superblock * SUPERBLOCK_BLOCKS * BLOCK_SIZE - ones
+            }
+        };
+
+        // Find the last superblock with at most `rank` ones/zeroes before it
+        let (mut left, mut right) = (0, self.sampled_ranks.len() - 1);
+        while left < right {
+            let mid = (left + right).div_ceil(2);
+            if sampled_rank(mid) <= rank {
+                left = mid;
+            } else {
+                right = mid - 1;
+            }
+        }
+        rank -= sampled_rank(left);
+
+        // Find the block containing the one/zero
+        let mut block = left * SUPERBLOCK_BLOCKS;
+        let mut position = unsafe { self.sampled_offsets.get_unchecked(left) };
+        loop {
+            let class = unsafe { self.classes.get_unchecked(block) };
+            let count = if TARGET { class } else { BLOCK_SIZE - class };
+            if count > rank {
+                break;
+            }
+            rank -= count;
+            position += Self::offset_width(class);
+            block += 1;
+        }
+
+        let (mut bits, _) = unsafe { self.block_at(block, position) };
+        if !TARGET {
+            bits = !bits;
+        }
+        // Find the correct bit inside the block by clearing all lower set bits
+        for _ in 0..rank {
+            bits &= bits - 1;
+        }
+        Some(block * BLOCK_SIZE + bits.trailing_zeros() as usize)
+    }
+}
+
+impl<const BLOCK_SIZE: usize> From<&BitVec> for Rrr<BLOCK_SIZE> {
+    fn from(bv: &BitVec) -> Self {
+        Self::new(bv)
+    }
+}
+
+impl<const BLOCK_SIZE: usize> BitGet for Rrr<BLOCK_SIZE> {
+    unsafe fn get_bit_unchecked(&self, index: usize) -> bool {
+        let block = index / BLOCK_SIZE;
+        let (position, _) = self.locate(block);
+        let (bits, _) = self.block_at(block, position);
+        (bits >> (index % BLOCK_SIZE)) & 1 == 1
+    }
+
+    fn get_bit(&self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("length is {} but index is {index}", self.len)
+        }
+        unsafe { self.get_bit_unchecked(index) }
+    }
+}
+
+impl<const BLOCK_SIZE: usize> RankSupport for Rrr<BLOCK_SIZE> {
+    fn rank<const TARGET: bool>(&self, index: usize) -> usize {
+        if index > self.len {
+            panic!("length is {} but index is {index}", self.len)
+        }
+        let block = index / BLOCK_SIZE;
+        let rest_bits = index % BLOCK_SIZE;
+        let (position, mut ones) = unsafe { self.locate(block) };
+        // If there are no rest bits, the block might be past the end of the bit vector
+        if rest_bits > 0 {
+            let (bits, _) = unsafe { self.block_at(block, position) };
+            ones += (bits & ((1 << rest_bits) - 1)).count_ones() as usize;
+        }
+
+        if TARGET {
+            ones
+        } else {
+            index - ones
+        }
+    }
+}
+
+impl<const BLOCK_SIZE: usize> SelectSupport<true> for Rrr<BLOCK_SIZE> {
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.select_impl::<true>(rank)
+    }
+}
+
+impl<const BLOCK_SIZE: usize> SelectSupport<false> for Rrr<BLOCK_SIZE> {
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.select_impl::<false>(rank)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bit_vec::{BitGet, BitVec};
+    use crate::rank_select::traits::{RankSupport, SelectSupport};
+    use crate::test_util::pattern;
+
+    use super::Rrr;
+
+    /// A bit vector with sparse, dense and random looking regions
+    fn regions(len: usize) -> BitVec {
+        let random: Vec<bool> = pattern(len, 2);
+        (0..len)
+            .map(|i| match (i / 2000) % 3 {
+                0 => i % 97 == 0,
+                1 => i % 11 != 0,
+                _ => random[i],
+            })
+            .collect()
+    }
+
+    fn matches_naive<const BLOCK_SIZE: usize>(bv: &BitVec) {
+        let rrr = Rrr::<BLOCK_SIZE>::new(bv);
+        assert_eq!(bv.len(), rrr.len());
+        assert_eq!(bv.count_ones(), rrr.number_of_ones());
+        let (mut ones, mut zeros) = (0, 0);
+        for i in 0..bv.len() {
+            assert_eq!(bv.get_bit(i), rrr.get_bit(i), "bit {i} incorrect");
+            assert_eq!(ones, rrr.rank::<true>(i), "rank of ones at {i}");
+            assert_eq!(zeros, rrr.rank::<false>(i), "rank of zeros at {i}");
+            if bv.get_bit(i) {
+                assert_eq!(Some(i), SelectSupport::<true>::select(&rrr, ones), "{ones}th one");
+                ones += 1;
+            } else {
+                assert_eq!(Some(i), SelectSupport::<false>::select(&rrr, zeros), "{zeros}th zero");
+                zeros += 1;
+            }
+        }
+        assert_eq!(ones, rrr.rank::<true>(bv.len()));
+        assert_eq!(zeros, rrr.rank::<false>(bv.len()));
+        assert_eq!(None, SelectSupport::<true>::select(&rrr, ones));
+        assert_eq!(None, SelectSupport::<false>::select(&rrr, zeros));
+    }
+
+    #[test]
+    fn block_sizes_test() {
+        let bv = regions(10000);
+        matches_naive::<1>(&bv);
+        matches_naive::<7>(&bv);
+        matches_naive::<15>(&bv);
+        matches_naive::<32>(&bv);
+        matches_naive::<63>(&bv);
+    }
+
+    #[test]
+    fn lengths_test() {
+        for len in [0, 1, 14, 15, 16, 15 * 32, 15 * 32 + 1, 15 * 64] {
+            matches_naive::<15>(&regions(len));
+        }
+    }
+
+    #[test]
+    fn encode_decode_test() {
+        for bits in (0u64..1 << 15).step_by(7) {
+            let class = bits.count_ones() as usize;
+            let offset = Rrr::<15>::encode(bits, class);
+            assert!(offset < super::BINOMIALS[15][class]);
+            assert_eq!(bits, Rrr::<15>::decode(offset, class));
+        }
+    }
+
+    #[test]
+    fn compression_test() {
+        let sparse: BitVec = (0..100_000).map(|i| i % 1000 == 0).collect();
+        let rrr = Rrr::<63>::new(&sparse);
+        assert!(rrr.offsets.len() < sparse.len() / 20);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds_test() {
+        Rrr::<15>::new(&BitVec::new(20)).get_bit(20);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rank_out_of_bounds_test() {
+        Rrr::<15>::new(&BitVec::new(20)).rank::<true>(21);
+    }
+}
@@ -29,7 +29,7 @@ pub trait RankSupport {
     /// bv.flip(15);
     /// bv.flip(20);
     ///
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     ///
     /// assert_eq!(0, rank_ds.rank::<true>(5));
     /// assert_eq!(0, rank_ds.rank::<true>(10));
@@ -67,7 +67,7 @@ pub trait RankSupport {
     /// bv.flip(15);
     /// bv.flip(20);
     ///
-    /// let rank_ds = FlatPopcount::<()>::new(&bv);
+    /// let rank_ds = FlatPopcount::<_, ()>::new(&bv);
     /// 
     /// assert_eq!(0, rank_ds.rank_dyn(5, true));
     /// assert_eq!(0, rank_ds.rank_dyn(10, true));
@@ -91,16 +91,41 @@ pub trait RankSupport {
 pub trait SelectSupport<const TARGET: bool> {
     /// Calculates the index of the nth time the given value shows up.
     ///
-    /// If [`TARGET`] is `true`, this will search for the nth one, if it is `false`, this will
+    /// If `TARGET` is `true`, this will search for the nth one, if it is `false`, this will
     /// search for zeroes.
     ///
     /// # Arguments
     ///
-    /// * `rank` - The rank of the zero/one to find.
+    /// * `rank` - The rank of the zero/one to find. The first zero/one has rank `0`.
+    ///
+    /// returns: The index of the zero/one or `None` if there are not enough zeroes/ones.
     ///
     /// # Examples
     ///
     /// ```
+    /// use succinct_neo::{
+    ///     bit_vec::BitVec,
+    ///     rank_select::{
+    ///         FlatPopcount,
+    ///         SelectSupport,
+    ///         flat_popcount::BinarySearch
+    ///     }
+    /// };
+    ///
+    /// let mut bv = BitVec::new(64);
+    ///
+    /// bv.flip(10);
+    /// bv.flip(15);
+    /// bv.flip(20);
+    ///
+    /// let select_ds = FlatPopcount::<_, BinarySearch>::new(&bv);
+    ///
+    /// assert_eq!(Some(10), SelectSupport::<true>::select(&select_ds, 0));
+    /// assert_eq!(Some(20), SelectSupport::<true>::select(&select_ds, 2));
+    /// assert_eq!(None, SelectSupport::<true>::select(&select_ds, 3));
+    ///
+    /// assert_eq!(Some(0), SelectSupport::<false>::select(&select_ds, 0));
+    /// assert_eq!(Some(11), SelectSupport::<false>::select(&select_ds, 10));
     /// ```
     fn select(&self, rank: usize) -> Option<usize>;
 }
@@ -0,0 +1,302 @@
+//! Shared parts of the binary format used to persist the data structures in this crate.
+//!
+//! Every serialized data structure starts with a header consisting of a 4 byte magic number
+//! identifying the data structure, followed by the format version as a little-endian `u32`.
+//! All integers after that are stored in little-endian byte order, so the format does not depend
+//! on the machine it was written on.
+//!
+//! Bit vectors and rank/select indices can also be used directly from a buffer, such as a memory
+//! mapped file, without copying them (see [`BitSlice::from_bytes`] and
+//! [`FlatPopcount::from_bytes`]). This requires the buffer to be aligned to 16 bytes and a
+//! little-endian target.
+//!
+//! Reading and writing data structures through `std::io` requires the `std` feature. The
+//! zero-copy views only need `alloc`.
+//!
+//! [`BitSlice::from_bytes`]: crate::bit_vec::BitSlice::from_bytes
+//! [`FlatPopcount::from_bytes`]: crate::rank_select::FlatPopcount::from_bytes
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::bit_vec::Word;
+
+/// The current version of the binary format.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The number of words to read or write at once.
+const CHUNK_WORDS: usize = 512;
+
+/// An error that occurs while reading a serialized data structure.
+#[derive(Debug)]
+pub enum SerializeError {
+    /// Reading from the underlying reader failed. This includes the input ending prematurely.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The input does not start with the magic number of the expected data structure.
+    InvalidMagic {
+        expected: [u8; 4],
+        found: [u8; 4],
+    },
+    /// The input was written in a version of the format which is not supported.
+    UnsupportedVersion(u32),
+    /// A buffer used for a zero-copy view is not aligned to the given number of bytes.
+    Misaligned(usize),
+    /// The input is malformed. The message describes which check failed.
+    ///
+    /// Without the `std` feature, this is also used if the input ends prematurely.
+    Invalid(&'static str),
+}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "i/o error: {e}"),
+            Self::InvalidMagic { expected, found } => write!(
+                f,
+                "invalid magic number: expected {:?} but found {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(found)
+            ),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported format version {version}, expected {FORMAT_VERSION}"
+            ),
+            Self::Misaligned(align) => write!(f, "buffer is not aligned to {align} bytes"),
+            Self::Invalid(msg) => write!(f, "invalid data: {msg}"),
+        }
+    }
+}
+
+impl core::error::Error for SerializeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for SerializeError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A source of serialized bytes.
+///
+/// With the `std` feature, this is implemented for every reader. Otherwise, it is only
+/// implemented for byte slices, which is all the zero-copy views need.
+pub(crate) trait ReadBytes {
+    /// Fills the buffer with the next bytes of the input.
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SerializeError>;
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + ?Sized> ReadBytes for R {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SerializeError> {
+        Ok(self.read_exact(buf)?)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ReadBytes for &[u8] {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SerializeError> {
+        if self.len() < buf.len() {
+            return Err(SerializeError::Invalid("unexpected end of input"));
+        }
+        let (bytes, rest) = self.split_at(buf.len());
+        buf.copy_from_slice(bytes);
+        *self = rest;
+        Ok(())
+    }
+}
+
+/// Writes the header for a data structure with the given magic number.
+#[cfg(feature = "std")]
+pub(crate) fn write_header(writer: &mut impl Write, magic: [u8; 4]) -> io::Result<()> {
+    writer.write_all(&magic)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())
+}
+
+/// Reads a header and checks that it has the given magic number and a supported version.
+pub(crate) fn read_header(
+    reader: &mut impl ReadBytes,
+    magic: [u8; 4],
+) -> Result<(), SerializeError> {
+    let mut found = [0; 4];
+    reader.read_bytes(&mut found)?;
+    if found != magic {
+        return Err(SerializeError::InvalidMagic {
+            expected: magic,
+            found,
+        });
+    }
+    let mut version = [0; 4];
+    reader.read_bytes(&mut version)?;
+    match u32::from_le_bytes(version) {
+        FORMAT_VERSION => Ok(()),
+        version => Err(SerializeError::UnsupportedVersion(version)),
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn read_u64(reader: &mut impl ReadBytes) -> Result<u64, SerializeError> {
+    let mut bytes = [0; 8];
+    reader.read_bytes(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads a `u64` which is used as a length or index in memory.
+pub(crate) fn read_usize(reader: &mut impl ReadBytes) -> Result<usize, SerializeError> {
+    usize::try_from(read_u64(reader)?)
+        .map_err(|_| SerializeError::Invalid("value does not fit into usize"))
+}
+
+/// Writes the given words in little-endian byte order.
+#[cfg(feature = "std")]
+pub(crate) fn write_words(writer: &mut impl Write, words: &[Word]) -> io::Result<()> {
+    let mut buf = [0u8; CHUNK_WORDS * 8];
+    for chunk in words.chunks(CHUNK_WORDS) {
+        for (bytes, &word) in buf.chunks_exact_mut(8).zip(chunk) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        writer.write_all(&buf[..chunk.len() * 8])?;
+    }
+    Ok(())
+}
+
+/// Reads the given number of words written by [`write_words`].
+///
+/// Memory is only allocated as the data is actually read, so a corrupted length does not cause
+/// a huge allocation up front.
+pub(crate) fn read_words(
+    reader: &mut impl ReadBytes,
+    len: usize,
+) -> Result<Vec<Word>, SerializeError> {
+    let mut words = Vec::with_capacity(len.min(CHUNK_WORDS));
+    let mut buf = [0u8; CHUNK_WORDS * 8];
+    while words.len() < len {
+        let chunk_len = (len - words.len()).min(CHUNK_WORDS);
+        let buf = &mut buf[..chunk_len * 8];
+        reader.read_bytes(buf)?;
+        words.extend(
+            buf.chunks_exact(8)
+                .map(|bytes| Word::from_le_bytes(bytes.try_into().unwrap())),
+        );
+    }
+    Ok(words)
+}
+
+/// Types for which every bit pattern is valid, so that they can be read directly from bytes.
+pub(crate) trait Plain: Copy {}
+
+impl Plain for Word {}
+impl Plain for u128 {}
+
+/// Reinterprets little-endian words stored in a buffer as a slice of words without copying.
+///
+/// returns: The words or an error if the buffer is misaligned, its length is not a multiple of
+/// the word size, or this is a big-endian target.
+pub(crate) fn cast_slice<T: Plain>(bytes: &[u8]) -> Result<&[T], SerializeError> {
+    if cfg!(target_endian = "big") {
+        return Err(SerializeError::Invalid("zero-copy views require a little-endian target"));
+    }
+    let size = core::mem::size_of::<T>();
+    let align = core::mem::align_of::<T>();
+    if !(bytes.as_ptr() as usize).is_multiple_of(align) {
+        return Err(SerializeError::Misaligned(align));
+    }
+    if !bytes.len().is_multiple_of(size) {
+        return Err(SerializeError::Invalid("length is not a multiple of the word size"));
+    }
+    // SAFETY: The pointer is aligned, the length is in bounds and every bit pattern is valid for T
+    Ok(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len() / size) })
+}
+
+/// A copy of a buffer which is aligned to 16 bytes, like a memory mapped file.
+#[cfg(all(test, feature = "std"))]
+pub(crate) struct AlignedBuf(Vec<u128>, usize);
+
+#[cfg(all(test, feature = "std"))]
+impl AlignedBuf {
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        let mut buf = Self(vec![0; bytes.len().div_ceil(16)], bytes.len());
+        // SAFETY: The vector contains at least as many bytes
+        unsafe { core::slice::from_raw_parts_mut(buf.0.as_mut_ptr().cast::<u8>(), buf.1) }
+            .copy_from_slice(bytes);
+        buf
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        // SAFETY: The vector contains at least as many bytes
+        unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), self.1) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cast_slice, read_header, SerializeError};
+    #[cfg(feature = "std")]
+    use super::{read_words, write_header, write_words};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn words_test() {
+        let words = (0..1500).map(|i| i * 0x0123_4567_89ab).collect::<Vec<u64>>();
+        let mut buf = Vec::new();
+        write_words(&mut buf, &words).unwrap();
+        assert_eq!(words.len() * 8, buf.len());
+        assert_eq!(0x89ab, u16::from_le_bytes([buf[8], buf[9]]));
+        assert_eq!(words, read_words(&mut buf.as_slice(), words.len()).unwrap());
+        assert!(read_words(&mut buf.as_slice(), words.len() + 1).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn header_test() {
+        let mut buf = Vec::new();
+        write_header(&mut buf, *b"TEST").unwrap();
+        assert!(read_header(&mut buf.as_slice(), *b"TEST").is_ok());
+        assert!(matches!(
+            read_header(&mut buf.as_slice(), *b"ELSE"),
+            Err(SerializeError::InvalidMagic { .. })
+        ));
+
+        buf[4] = 2;
+        assert!(matches!(
+            read_header(&mut buf.as_slice(), *b"TEST"),
+            Err(SerializeError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            read_header(&mut &buf[..6], *b"TEST"),
+            Err(SerializeError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn cast_slice_test() {
+        let words = [0x0123_4567_89ab_cdefu64, 42];
+        let bytes = unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), 16) };
+        assert_eq!(&words, cast_slice::<u64>(bytes).unwrap());
+        assert_eq!(&words[1..], cast_slice::<u64>(&bytes[8..]).unwrap());
+        assert!(matches!(cast_slice::<u64>(&bytes[1..9]), Err(SerializeError::Misaligned(_))));
+        assert!(matches!(cast_slice::<u64>(&bytes[..12]), Err(SerializeError::Invalid(_))));
+    }
+
+    #[test]
+    fn read_slice_test() {
+        let bytes = *b"TEST\x01\x00\x00\x00";
+        assert!(read_header(&mut bytes.as_slice(), *b"TEST").is_ok());
+        assert!(read_header(&mut &bytes[..6], *b"TEST").is_err());
+    }
+}
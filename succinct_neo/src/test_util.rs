@@ -0,0 +1,34 @@
+//! Deterministic inputs shared by the unit tests.
+
+use crate::bit_vec::Word;
+
+/// A xorshift pseudo-random number generator with a fixed seed, so that tests are reproducible.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new() -> Self {
+        Self(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Gets the next pseudo-random word.
+    pub(crate) fn next_word(&mut self) -> Word {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Gets pseudo-random integers of the given width between 1 and 64 bits, taken from the upper
+    /// bits of the next words.
+    pub(crate) fn values(&mut self, width: usize, len: usize) -> Vec<Word> {
+        (0..len)
+            .map(|_| self.next_word() >> (Word::BITS as usize - width))
+            .collect()
+    }
+}
+
+/// A bit pattern which does not repeat with the word size, in which roughly every `modulus`-th
+/// bit is set.
+pub(crate) fn pattern<B: FromIterator<bool>>(len: usize, modulus: usize) -> B {
+    (0..len).map(|i| (i * i + i / 3) % modulus == 0).collect()
+}
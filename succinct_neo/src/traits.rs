@@ -1,14 +1,17 @@
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
 
 use num::{
     traits::{NumAssignOps, NumOps},
-    FromPrimitive, Integer, ToPrimitive, Unsigned,
+    FromPrimitive, Integer, PrimInt, ToPrimitive, Unsigned,
 };
 
+use crate::bit_vec::Word;
+
 /// A trait supporting basic primitve integer operations, used for block types in compressed data structures.
 pub trait BlockType:
     Unsigned
     + Integer
+    + PrimInt
     + Copy
     + FromPrimitive
     + ToPrimitive
@@ -21,12 +24,34 @@ pub trait BlockType:
     + BitAndAssign
     + BitXorAssign
 {
+    /// The number of bits in a block.
+    const BITS: usize;
+
+    /// Converts the lowest bits of a word into a block, discarding the bits which do not fit.
+    fn from_word(word: Word) -> Self;
+
+    /// Converts a block into a word, discarding the bits which do not fit.
+    fn to_word(self) -> Word;
 }
 
-impl BlockType for usize {}
-impl BlockType for u128 {}
-impl BlockType for u64 {}
-impl BlockType for u32 {}
-impl BlockType for u16 {}
-impl BlockType for u8 {}
+macro_rules! block_type {
+    ($($tp:ty),+) => {
+        $(
+            impl BlockType for $tp {
+                const BITS: usize = <$tp>::BITS as usize;
+
+                #[inline]
+                fn from_word(word: Word) -> Self {
+                    word as $tp
+                }
+
+                #[inline]
+                fn to_word(self) -> Word {
+                    self as Word
+                }
+            }
+        )+
+    };
+}
 
+block_type! { usize, u128, u64, u32, u16, u8 }
@@ -0,0 +1,368 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::bit_vec::{BitGet, BitModify, BitVec};
+use crate::int_vec::IntVec;
+use crate::rank_select::flat_popcount::{LinearSearch, SelectStrategy};
+use crate::rank_select::{FlatPopcount, RankSupport, SelectSupport};
+
+/// A wavelet matrix as described by Claude, Navarro and Ordóñez in *The wavelet matrix: An
+/// efficient wavelet tree for large alphabets*.
+///
+/// It supports access, rank and select queries over a sequence of integers, as well as
+/// quantile and range counting queries over ranges of the sequence.
+/// There is one level per bit of the integers, starting at the most significant bit. Each level
+/// is a bit vector containing the corresponding bit of every integer, after the integers have
+/// been stably sorted by their more significant bits. The levels are indexed using
+/// [`FlatPopcount`] with the select strategy `Strat`.
+///
+/// # Examples
+///
+/// ```
+/// use succinct_neo::{int_vec::IntVec, wavelet_matrix::WaveletMatrix};
+///
+/// let mut v = IntVec::new(3);
+/// for x in [5, 1, 4, 1, 7, 2] {
+///     v.push(x);
+/// }
+///
+/// let wm: WaveletMatrix = WaveletMatrix::new(&v);
+/// assert_eq!(4, wm.access(2));
+/// assert_eq!(2, wm.rank(1, 4));
+/// assert_eq!(Some(3), wm.select(1, 1));
+/// // The second smallest integer in positions 0 to 3
+/// assert_eq!(Some(1), wm.quantile(0..4, 1));
+/// // The number of integers in 1..5 in positions 1 to 5
+/// assert_eq!(4, wm.range_count(1..6, 1..5));
+/// ```
+pub struct WaveletMatrix<Strat = LinearSearch> {
+    /// The bit vectors of all levels, starting with the most significant bit
+    levels: Vec<FlatPopcount<BitVec, Strat>>,
+    /// The number of zeroes in each level
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl<Strat: SelectStrategy> WaveletMatrix<Strat> {
+    /// Creates a new wavelet matrix containing the integers of an [`IntVec`].
+    ///
+    /// The number of levels is the width of the integers in the vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The integers to store.
+    pub fn new(values: &IntVec) -> Self {
+        let width = values.width();
+        let mut current = values.iter().collect::<Vec<_>>();
+        let len = current.len();
+        let mut levels = Vec::with_capacity(width);
+        let mut zeros = Vec::with_capacity(width);
+
+        for level in 0..width {
+            let shift = width - 1 - level;
+            let mut bits = BitVec::new(len);
+            for (i, &v) in current.iter().enumerate() {
+                bits.set_bit(i, (v >> shift) & 1 == 1);
+            }
+            // Stably move all integers with a zero in this level to the front
+            let (mut next, ones): (Vec<_>, Vec<_>) =
+                current.iter().partition(|&&v| (v >> shift) & 1 == 0);
+            zeros.push(next.len());
+            next.extend(ones);
+            current = next;
+            levels.push(FlatPopcount::new(bits));
+        }
+
+        Self { levels, zeros, len }
+    }
+
+    /// Gets the number of integers in this wavelet matrix.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this wavelet matrix contains no integers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the number of levels, i.e. the number of bits per integer.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Maps an index in a level to the corresponding index in the next level.
+    #[inline]
+    fn next_index(&self, level: usize, index: usize, bit: bool) -> usize {
+        if bit {
+            self.zeros[level] + self.levels[level].rank::<true>(index)
+        } else {
+            self.levels[level].rank::<false>(index)
+        }
+    }
+
+    /// Gets the bit of the integer which corresponds to the given level.
+    #[inline]
+    fn bit(&self, value: usize, level: usize) -> bool {
+        (value >> (self.width() - 1 - level)) & 1 == 1
+    }
+
+    /// Returns `true` if the integer has more bits than the levels of this wavelet matrix.
+    #[inline]
+    fn too_large(&self, value: usize) -> bool {
+        self.width() < usize::BITS as usize && value >> self.width() != 0
+    }
+
+    /// Gets the integer at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the integer.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn access(&self, mut index: usize) -> usize {
+        if index >= self.len {
+            panic!("length is {} but index is {index}", self.len)
+        }
+        let mut value = 0;
+        for level in 0..self.width() {
+            let bit = self.levels[level].backing().get_bit(index);
+            value = (value << 1) | bit as usize;
+            index = self.next_index(level, index, bit);
+        }
+        value
+    }
+
+    /// Counts the occurrences of an integer up to and not including the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The integer to count.
+    /// * `index` - The index up to which to count.
+    ///
+    /// # Panics
+    ///
+    /// If the index is greater than the length.
+    pub fn rank(&self, value: usize, index: usize) -> usize {
+        if index > self.len {
+            panic!("length is {} but index is {index}", self.len)
+        }
+        if index == 0 || self.too_large(value) {
+            return 0;
+        }
+        let (mut start, mut end) = (0, index);
+        for level in 0..self.width() {
+            let bit = self.bit(value, level);
+            start = self.next_index(level, start, bit);
+            end = self.next_index(level, end, bit);
+        }
+        end - start
+    }
+
+    /// Finds the position of the occurrence of an integer with the given rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The integer to find.
+    /// * `rank` - The rank of the occurrence to find. The first occurrence has rank `0`.
+    ///
+    /// returns: The index of the occurrence or `None` if there are not enough occurrences.
+    pub fn select(&self, value: usize, rank: usize) -> Option<usize> {
+        if rank >= self.rank(value, self.len) {
+            return None;
+        }
+        // Find where the occurrences of the integer start in the last level
+        let mut start = 0;
+        for level in 0..self.width() {
+            start = self.next_index(level, start, self.bit(value, level));
+        }
+        // Follow the occurrence back up to the first level
+        let mut index = start + rank;
+        for level in (0..self.width()).rev() {
+            index = if self.bit(value, level) {
+                SelectSupport::<true>::select(&self.levels[level], index - self.zeros[level])?
+            } else {
+                SelectSupport::<false>::select(&self.levels[level], index)?
+            };
+        }
+        Some(index)
+    }
+
+    /// Finds the `k`-th smallest integer in a range of this wavelet matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of indices to search.
+    /// * `k` - The rank of the integer in sorted order. The smallest integer has rank `0`.
+    ///
+    /// returns: The integer or `None` if the range contains no more than `k` integers.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    pub fn quantile(&self, range: Range<usize>, mut k: usize) -> Option<usize> {
+        self.check_range(&range);
+        let Range { mut start, mut end } = range;
+        if k >= end.saturating_sub(start) {
+            return None;
+        }
+        let mut value = 0;
+        for level in 0..self.width() {
+            let zeros =
+                self.levels[level].rank::<false>(end) - self.levels[level].rank::<false>(start);
+            let bit = k >= zeros;
+            if bit {
+                k -= zeros;
+            }
+            value = (value << 1) | bit as usize;
+            start = self.next_index(level, start, bit);
+            end = self.next_index(level, end, bit);
+        }
+        Some(value)
+    }
+
+    /// Counts the integers in a range of this wavelet matrix whose values lie in a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of indices to search.
+    /// * `values` - The range of values to count.
+    ///
+    /// # Panics
+    ///
+    /// If the range of indices is out of bounds.
+    pub fn range_count(&self, range: Range<usize>, values: Range<usize>) -> usize {
+        self.check_range(&range);
+        if values.start >= values.end {
+            return 0;
+        }
+        self.count_less(range.clone(), values.end) - self.count_less(range, values.start)
+    }
+
+    /// Counts the integers in a range of indices that are less than the given value.
+    fn count_less(&self, range: Range<usize>, value: usize) -> usize {
+        let Range { mut start, mut end } = range;
+        if start >= end {
+            return 0;
+        }
+        if self.too_large(value) {
+            return end - start;
+        }
+        let mut count = 0;
+        for level in 0..self.width() {
+            let bit = self.bit(value, level);
+            if bit {
+                // All integers with a zero in this level are smaller
+                count +=
+                    self.levels[level].rank::<false>(end) - self.levels[level].rank::<false>(start);
+            }
+            start = self.next_index(level, start, bit);
+            end = self.next_index(level, end, bit);
+        }
+        count
+    }
+
+    #[inline]
+    fn check_range(&self, range: &Range<usize>) {
+        if range.end > self.len {
+            panic!("length is {} but range is {range:?}", self.len)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::int_vec::{IntAccess, IntVec};
+    use crate::rank_select::flat_popcount::{BinarySearch, LinearSearch};
+
+    use super::WaveletMatrix;
+
+    fn values(width: usize, len: usize) -> IntVec {
+        let mut v = IntVec::new(width);
+        for i in 0..len {
+            v.push((i * i + 7 * i + i / 13) % (1 << width));
+        }
+        v
+    }
+
+    #[test]
+    fn access_test() {
+        let v = values(5, 3000);
+        let wm = WaveletMatrix::<LinearSearch>::new(&v);
+        assert_eq!(3000, wm.len());
+        assert_eq!(5, wm.width());
+        for i in 0..wm.len() {
+            assert_eq!(v.get(i), wm.access(i), "value at {i} incorrect");
+        }
+    }
+
+    #[test]
+    fn rank_select_test() {
+        let v = values(4, 3000);
+        let wm = WaveletMatrix::<BinarySearch>::new(&v);
+        for symbol in 0..17 {
+            let mut count = 0;
+            for i in 0..wm.len() {
+                assert_eq!(count, wm.rank(symbol, i), "rank of {symbol} at {i}");
+                if v.get(i) == symbol {
+                    assert_eq!(Some(i), wm.select(symbol, count), "{count}th {symbol}");
+                    count += 1;
+                }
+            }
+            assert_eq!(count, wm.rank(symbol, wm.len()));
+            assert_eq!(None, wm.select(symbol, count));
+        }
+    }
+
+    #[test]
+    fn quantile_test() {
+        let v = values(6, 500);
+        let wm = WaveletMatrix::<LinearSearch>::new(&v);
+        for (start, end) in [(0, 500), (13, 14), (100, 377), (250, 250)] {
+            let mut sorted = (start..end).map(|i| v.get(i)).collect::<Vec<_>>();
+            sorted.sort();
+            for k in 0..=sorted.len() {
+                assert_eq!(
+                    sorted.get(k).copied(),
+                    wm.quantile(start..end, k),
+                    "{k} in {start}..{end}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn range_count_test() {
+        let v = values(6, 500);
+        let wm = WaveletMatrix::<LinearSearch>::new(&v);
+        for (start, end) in [(0, 500), (13, 14), (100, 377), (250, 250)] {
+            for (lo, hi) in [(0, 64), (0, 1000), (10, 20), (5, 5), (63, 64), (30, 10)] {
+                let expected = (start..end)
+                    .filter(|&i| (lo..hi).contains(&v.get(i)))
+                    .count();
+                assert_eq!(expected, wm.range_count(start..end, lo..hi));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_test() {
+        let wm = WaveletMatrix::<LinearSearch>::new(&IntVec::new(4));
+        assert!(wm.is_empty());
+        assert_eq!(0, wm.rank(3, 0));
+        assert_eq!(None, wm.select(3, 0));
+        assert_eq!(None, wm.quantile(0..0, 0));
+        assert_eq!(0, wm.range_count(0..0, 0..16));
+    }
+
+    #[test]
+    #[should_panic]
+    fn access_out_of_bounds_test() {
+        WaveletMatrix::<LinearSearch>::new(&values(3, 10)).access(10);
+    }
+}